@@ -0,0 +1,227 @@
+use crate::{Error, Part};
+use std::{collections::HashMap, fmt::Display, fs::read_to_string, path::Path};
+
+/// The manifest that `aoc run --check` looks for when no other one is given.
+pub const DEFAULT_ANSWERS_PATH: &str = "answers.toml";
+
+/// The accepted answers of each day, read from a TOML manifest of the form:
+///
+/// ```toml
+/// [day1]
+/// part1 = "1393"
+/// part2 = "1359"
+/// ```
+///
+/// Answers are compared as strings; numbers are accepted for convenience, and multi-line answers
+/// are compared without their trailing whitespace.
+#[derive(Debug, Default)]
+pub struct Answers {
+    answers: HashMap<(usize, Part), String>,
+}
+
+impl Answers {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let contents = read_to_string(path).map_err(|source| {
+            Error::Io {
+                path: path.to_path_buf(),
+                source,
+            }
+            .to_string()
+        })?;
+
+        Self::parse(&contents).map_err(|e| format!("could not parse '{}': {}", path.display(), e))
+    }
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let manifest = s.parse::<toml::Value>().map_err(|e| e.to_string())?;
+        let days = manifest.as_table().ok_or("expected a table of days")?;
+
+        let mut answers = HashMap::new();
+        for (day_key, parts) in days {
+            let day = day_key
+                .strip_prefix("day")
+                .and_then(|day| day.parse::<usize>().ok())
+                .ok_or_else(|| format!("'{}' is not a day of the form 'dayN'", day_key))?;
+            let parts = parts
+                .as_table()
+                .ok_or_else(|| format!("'{}' is not a table of parts", day_key))?;
+
+            for (part_key, answer) in parts {
+                let part = match part_key.as_str() {
+                    "part1" => Part::One,
+                    "part2" => Part::Two,
+                    _ => {
+                        return Err(format!(
+                            "'{}.{}' is neither 'part1' nor 'part2'",
+                            day_key, part_key
+                        ))
+                    }
+                };
+                let answer = match answer {
+                    toml::Value::String(answer) => answer.trim_end().to_string(),
+                    toml::Value::Integer(answer) => answer.to_string(),
+                    _ => {
+                        return Err(format!(
+                            "'{}.{}' is neither a string nor an integer",
+                            day_key, part_key
+                        ))
+                    }
+                };
+                answers.insert((day, part), answer);
+            }
+        }
+
+        Ok(Self { answers })
+    }
+
+    pub fn get(&self, day: usize, part: Part) -> Option<&str> {
+        self.answers.get(&(day, part)).map(String::as_str)
+    }
+
+    pub fn check(&self, day: usize, part: Part, answer: &str) -> Outcome {
+        let answer = answer.trim_end();
+        match self.get(day, part) {
+            Some(expected) if expected == answer => Outcome::Pass,
+            Some(expected) => Outcome::Mismatch {
+                expected: expected.to_string(),
+                actual: answer.to_string(),
+            },
+            None => Outcome::Unrecorded(answer.to_string()),
+        }
+    }
+}
+
+/// The result of checking a single part against the manifest.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    Mismatch {
+        expected: String,
+        actual: String,
+    },
+    /// The part was solved, but the manifest has no answer to compare it to.
+    Unrecorded(String),
+    /// The part could not be solved at all, e.g. because its input is missing or malformed.
+    Fail(String),
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Pass => write!(f, "pass"),
+            // multi-line answers, like the folded paper of day 13, start on their own line
+            Outcome::Mismatch { expected, actual }
+                if expected.contains('\n') || actual.contains('\n') =>
+            {
+                write!(f, "MISMATCH, expected:\n{}\nbut got:\n{}", expected, actual)
+            }
+            Outcome::Mismatch { expected, actual } => {
+                write!(f, "MISMATCH, expected {} but got {}", expected, actual)
+            }
+            Outcome::Unrecorded(actual) if actual.contains('\n') => {
+                write!(f, "no recorded answer, got:\n{}", actual)
+            }
+            Outcome::Unrecorded(actual) => write!(f, "no recorded answer, got {}", actual),
+            Outcome::Fail(reason) => write!(f, "FAIL, {}", reason),
+        }
+    }
+}
+
+/// Running totals of the outcomes of a check.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub passed: usize,
+    pub mismatched: usize,
+    pub failed: usize,
+    pub unrecorded: usize,
+}
+
+impl Summary {
+    pub fn record(&mut self, outcome: &Outcome) {
+        match outcome {
+            Outcome::Pass => self.passed += 1,
+            Outcome::Mismatch { .. } => self.mismatched += 1,
+            Outcome::Unrecorded(_) => self.unrecorded += 1,
+            Outcome::Fail(_) => self.failed += 1,
+        }
+    }
+
+    pub fn has_regressions(&self) -> bool {
+        self.mismatched > 0 || self.failed > 0
+    }
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} passed, {} mismatched, {} failed, {} without a recorded answer",
+            self.passed, self.mismatched, self.failed, self.unrecorded
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"
+[day1]
+part1 = "1393"
+part2 = 1359
+
+[day13]
+part1 = "765"
+part2 = """
+#..#
+####
+"""
+"#;
+
+    #[test]
+    fn parse_manifest() {
+        let answers = Answers::parse(MANIFEST).unwrap();
+        assert_eq!(answers.get(1, Part::One), Some("1393"));
+        assert_eq!(answers.get(1, Part::Two), Some("1359"));
+        assert_eq!(answers.get(13, Part::Two), Some("#..#\n####"));
+        assert_eq!(answers.get(2, Part::One), None);
+    }
+
+    #[test]
+    fn parse_rejects_unknown_keys() {
+        assert!(Answers::parse("[one]\npart1 = \"1\"\n").is_err());
+        assert!(Answers::parse("[day1]\npart3 = \"1\"\n").is_err());
+        assert!(Answers::parse("[day1]\npart1 = [1]\n").is_err());
+    }
+
+    #[test]
+    fn check_against_manifest() {
+        let answers = Answers::parse(MANIFEST).unwrap();
+        assert_eq!(answers.check(1, Part::One, "1393"), Outcome::Pass);
+        assert_eq!(answers.check(13, Part::Two, "#..#\n####\n"), Outcome::Pass);
+        assert_eq!(
+            answers.check(1, Part::Two, "1358"),
+            Outcome::Mismatch {
+                expected: "1359".to_string(),
+                actual: "1358".to_string()
+            }
+        );
+        assert_eq!(
+            answers.check(2, Part::One, "42"),
+            Outcome::Unrecorded("42".to_string())
+        );
+    }
+
+    #[test]
+    fn only_mismatches_and_failures_are_regressions() {
+        let mut summary = Summary::default();
+        summary.record(&Outcome::Pass);
+        summary.record(&Outcome::Unrecorded("42".to_string()));
+        assert!(!summary.has_regressions());
+
+        summary.record(&Outcome::Fail("missing input".to_string()));
+        assert!(summary.has_regressions());
+        assert_eq!(summary.failed, 1);
+    }
+}
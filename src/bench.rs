@@ -0,0 +1,233 @@
+use crate::{DynSolution, Error, Params, Part};
+use serde_json::json;
+use std::{
+    fmt::Display,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+/// A separately timed step of solving a day.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Phase {
+    Parse,
+    Solve(Part),
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Phase::Parse => write!(f, "parse"),
+            Phase::Solve(part) => write!(f, "part{}", part),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+}
+
+impl Stats {
+    /// Summarises the timings of each iteration, returning `None` if there are none.
+    pub fn from_samples(samples: &[Duration]) -> Option<Self> {
+        let mut samples = samples.to_vec();
+        samples.sort_unstable();
+
+        let len = samples.len();
+        let min = *samples.first()?;
+        let median = if len % 2 == 1 {
+            samples[len / 2]
+        } else {
+            (samples[len / 2 - 1] + samples[len / 2]) / 2
+        };
+        let mean = samples.iter().sum::<Duration>() / len as u32;
+
+        Some(Self { min, median, mean })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Measurement {
+    pub day: usize,
+    pub phase: Phase,
+    pub iterations: usize,
+    pub stats: Stats,
+}
+
+/// Times parsing `input` and then solving each part of `day` on it with `params`, `iterations`
+/// times each.
+pub fn bench_day(
+    day: usize,
+    solution: &dyn DynSolution,
+    input: &str,
+    params: &Params,
+    iterations: usize,
+) -> Result<Vec<Measurement>, Error> {
+    assert!(iterations > 0, "at least one iteration is needed");
+
+    let mut parse_samples = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let parsed = solution.parse(input)?;
+        parse_samples.push(start.elapsed());
+        drop(parsed);
+    }
+    let mut measurements = vec![measurement(day, Phase::Parse, &parse_samples)];
+
+    let parsed = solution.parse_with(input, params.clone())?;
+    let parts = if solution.has_part2() {
+        vec![Part::One, Part::Two]
+    } else {
+        vec![Part::One]
+    };
+    for part in parts {
+        let samples = (0..iterations)
+            .map(|_| {
+                let start = Instant::now();
+                let answer = parsed.solve(part);
+                let elapsed = start.elapsed();
+                drop(answer);
+                elapsed
+            })
+            .collect::<Vec<_>>();
+        measurements.push(measurement(day, Phase::Solve(part), &samples));
+    }
+
+    Ok(measurements)
+}
+
+fn measurement(day: usize, phase: Phase, samples: &[Duration]) -> Measurement {
+    Measurement {
+        day,
+        phase,
+        iterations: samples.len(),
+        stats: Stats::from_samples(samples).unwrap(),
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!(
+                "'{}' is not a valid format, expected text, json or csv",
+                s
+            )),
+        }
+    }
+}
+
+/// Formats the measurements as a human-readable table, a JSON array or CSV with a header row;
+/// the latter two report durations in nanoseconds.
+pub fn format_measurements(measurements: &[Measurement], format: Format) -> String {
+    match format {
+        Format::Text => measurements
+            .iter()
+            .map(|m| {
+                format!(
+                    "Day {:<2} {:<5}  min {:>12}  median {:>12}  mean {:>12}\n",
+                    m.day,
+                    m.phase.to_string(),
+                    format!("{:?}", m.stats.min),
+                    format!("{:?}", m.stats.median),
+                    format!("{:?}", m.stats.mean)
+                )
+            })
+            .collect(),
+        Format::Json => {
+            let records = measurements
+                .iter()
+                .map(|m| {
+                    json!({
+                        "day": m.day,
+                        "phase": m.phase.to_string(),
+                        "iterations": m.iterations,
+                        "min_ns": m.stats.min.as_nanos() as u64,
+                        "median_ns": m.stats.median.as_nanos() as u64,
+                        "mean_ns": m.stats.mean.as_nanos() as u64,
+                    })
+                })
+                .collect::<Vec<_>>();
+            let mut json = serde_json::to_string_pretty(&records).unwrap();
+            json.push('\n');
+            json
+        }
+        Format::Csv => {
+            let mut csv = "day,phase,iterations,min_ns,median_ns,mean_ns\n".to_string();
+            for m in measurements {
+                csv += &format!(
+                    "{},{},{},{},{},{}\n",
+                    m.day,
+                    m.phase,
+                    m.iterations,
+                    m.stats.min.as_nanos(),
+                    m.stats.median.as_nanos(),
+                    m.stats.mean.as_nanos()
+                );
+            }
+            csv
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(samples: &[u64]) -> Vec<Duration> {
+        samples
+            .iter()
+            .map(|&ms| Duration::from_millis(ms))
+            .collect()
+    }
+
+    #[test]
+    fn stats_of_odd_number_of_samples() {
+        let stats = Stats::from_samples(&millis(&[5, 1, 3])).unwrap();
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.median, Duration::from_millis(3));
+        assert_eq!(stats.mean, Duration::from_millis(3));
+    }
+
+    #[test]
+    fn stats_of_even_number_of_samples() {
+        let stats = Stats::from_samples(&millis(&[4, 1, 9, 2])).unwrap();
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.median, Duration::from_millis(3));
+        assert_eq!(stats.mean, Duration::from_millis(4));
+        assert!(Stats::from_samples(&[]).is_none());
+    }
+
+    #[test]
+    fn format_as_csv_and_json() {
+        let measurements = [Measurement {
+            day: 19,
+            phase: Phase::Solve(Part::Two),
+            iterations: 3,
+            stats: Stats::from_samples(&millis(&[1, 2, 3])).unwrap(),
+        }];
+
+        assert_eq!(
+            format_measurements(&measurements, Format::Csv),
+            "day,phase,iterations,min_ns,median_ns,mean_ns\n19,part2,3,1000000,2000000,2000000\n"
+        );
+
+        let json = format_measurements(&measurements, Format::Json);
+        let records: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(records[0]["phase"], "part2");
+        assert_eq!(records[0]["median_ns"], 2_000_000);
+    }
+}
@@ -0,0 +1,207 @@
+use crate::{Params, Part};
+use std::{
+    env, fs,
+    hash::Hasher,
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Environment variable that points to the directory the cached answers are kept in.
+pub const CACHE_DIR_VAR: &str = "AOC_CACHE_DIR";
+
+const DEFAULT_CACHE_DIR: &str = "cache";
+
+/// The 64-bit FNV-1a hash, which unlike `std::collections::hash_map::DefaultHasher` is guaranteed
+/// to stay the same between Rust versions, so cached answers survive a toolchain update.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// The version of the running binary: its package version, along with a hash of the executable so
+/// that rebuilding it after changing a solution never serves an answer of the old one.
+pub fn binary_version() -> String {
+    let executable_hash = env::current_exe()
+        .and_then(fs::read)
+        .map(|executable| {
+            let mut hasher = Fnv1a::default();
+            hasher.write(&executable);
+            format!("{:016x}", hasher.finish())
+        })
+        .unwrap_or_default();
+
+    format!("{}+{}", env!("CARGO_PKG_VERSION"), executable_hash)
+}
+
+/// Answers computed before, kept on disk a file per answer. An answer is keyed by its day and
+/// part, a hash of the input and params it was computed with, and the version of the binary that
+/// computed it, so a change to any of them is a miss rather than a stale answer.
+pub struct Cache {
+    dir: PathBuf,
+    version: String,
+}
+
+impl Cache {
+    pub fn new(dir: impl Into<PathBuf>, version: impl Into<String>) -> Self {
+        Self {
+            dir: dir.into(),
+            version: version.into(),
+        }
+    }
+
+    /// The directory in [`CACHE_DIR_VAR`], or `cache` if it is not set.
+    pub fn default_dir() -> PathBuf {
+        env::var_os(CACHE_DIR_VAR).map_or_else(|| PathBuf::from(DEFAULT_CACHE_DIR), PathBuf::from)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn get(&self, day: usize, part: Part, input: &str, params: &Params) -> Option<String> {
+        fs::read_to_string(self.path(day, part, input, params)).ok()
+    }
+
+    pub fn insert(
+        &self,
+        day: usize,
+        part: Part,
+        input: &str,
+        params: &Params,
+        answer: &str,
+    ) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.path(day, part, input, params), answer)
+    }
+
+    /// Removes every answer cached in `dir`, of every version. Only the files named like answers
+    /// are removed, as the directory can be any the user points [`CACHE_DIR_VAR`] at.
+    pub fn clear(dir: &Path) -> io::Result<()> {
+        let entries = match fs::read_dir(dir) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            entries => entries?,
+        };
+        for entry in entries {
+            let entry = entry?;
+            let is_answer = entry
+                .file_name()
+                .to_str()
+                .map_or(false, is_answer_file_name);
+            if is_answer && entry.file_type()?.is_file() {
+                fs::remove_file(entry.path())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn path(&self, day: usize, part: Part, input: &str, params: &Params) -> PathBuf {
+        let mut hasher = Fnv1a::default();
+        // the separators keep the boundaries between the fields from shifting
+        for field in [&self.version, input, &params.to_string()] {
+            hasher.write(field.as_bytes());
+            hasher.write(&[0]);
+        }
+
+        self.dir
+            .join(format!("day{}-part{}-{:016x}", day, part, hasher.finish()))
+    }
+}
+
+/// Whether `name` is of the form `Cache::path` names answers, like `day3-part1-0123456789abcdef`.
+fn is_answer_file_name(name: &str) -> bool {
+    let is_number = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    match name.split('-').collect::<Vec<_>>()[..] {
+        [day, part, hash] => {
+            day.strip_prefix("day").map_or(false, is_number)
+                && part.strip_prefix("part").map_or(false, is_number)
+                && hash.len() == 16
+                && hash.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Param;
+
+    const STEPS: Param = Param {
+        name: "steps",
+        default: 100,
+        description: "the steps to take",
+        check: Param::any_value,
+    };
+
+    fn cache_in(name: &str, version: &str) -> Cache {
+        let dir = env::temp_dir().join(format!("aoc-cache-{}-{}", name, std::process::id()));
+        Cache::new(dir, version)
+    }
+
+    #[test]
+    fn answers_are_found_under_the_same_key_only() {
+        let cache = cache_in("keys", "1");
+        let params = Params::default();
+        cache.insert(1, Part::One, "1\n2\n", &params, "7").unwrap();
+
+        assert_eq!(
+            cache.get(1, Part::One, "1\n2\n", &params),
+            Some("7".to_string())
+        );
+        assert_eq!(cache.get(1, Part::Two, "1\n2\n", &params), None);
+        assert_eq!(cache.get(2, Part::One, "1\n2\n", &params), None);
+        assert_eq!(cache.get(1, Part::One, "1\n3\n", &params), None);
+        let steps = Params::parse(&[STEPS], &["steps=5"]).unwrap();
+        assert_eq!(cache.get(1, Part::One, "1\n2\n", &steps), None);
+        let other_version = Cache::new(cache.dir(), "2");
+        assert_eq!(other_version.get(1, Part::One, "1\n2\n", &params), None);
+
+        Cache::clear(cache.dir()).unwrap();
+    }
+
+    #[test]
+    fn clearing_removes_every_answer() {
+        let cache = cache_in("clear", "1");
+        let params = Params::default();
+        cache.insert(3, Part::Two, "x", &params, "42").unwrap();
+
+        Cache::clear(cache.dir()).unwrap();
+        assert_eq!(cache.get(3, Part::Two, "x", &params), None);
+        // clearing an empty cache is not an error
+        Cache::clear(cache.dir()).unwrap();
+    }
+
+    #[test]
+    fn clearing_leaves_everything_but_answers_alone() {
+        let cache = cache_in("others", "1");
+        let params = Params::default();
+        cache.insert(3, Part::Two, "x", &params, "42").unwrap();
+        let others = ["notes.txt", "day3-part2", "day3-part2-not-a-hash"];
+        for name in others {
+            fs::write(cache.dir().join(name), "keep").unwrap();
+        }
+
+        Cache::clear(cache.dir()).unwrap();
+        assert_eq!(cache.get(3, Part::Two, "x", &params), None);
+        for name in others {
+            assert!(cache.dir().join(name).is_file(), "{} was removed", name);
+        }
+
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+}
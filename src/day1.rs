@@ -1,5 +1,7 @@
-use advent_of_code_2021::read_file_lines_as;
+use crate::generate::InputRng;
+use crate::{try_parse_lines_as, Error, Solution};
 use itertools::Itertools;
+use rand::Rng;
 
 fn find_num_of_increases(measurements: &Vec<u32>) -> u32 {
     /*let mut num_of_increases = 0;
@@ -56,17 +58,72 @@ fn find_num_of_increases_sliding_window(measurements: &Vec<u32>) -> u32 {
     )
 }
 
-fn main() {
-    let measurements = read_file_lines_as("input/day1.txt", |l| l.parse::<u32>().unwrap());
-
-    let num_of_increases = find_num_of_increases(&measurements);
-    println!(
-        "{} measurements are larger than the previous measurement.",
-        num_of_increases
-    );
-    let num_of_increases_sliding_window = find_num_of_increases_sliding_window(&measurements);
-    println!(
-        "{} measurement windows are larger than the previous measurement window.",
-        num_of_increases_sliding_window
-    );
+/// Generates `size` depth measurements, wandering downwards like the sea floor does.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    let mut depth = rng.gen_range(100..200);
+    (0..size)
+        .map(|_| {
+            depth = (depth + rng.gen_range(-10..=20)).max(0);
+            format!("{}\n", depth)
+        })
+        .collect()
+}
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    type Input = Vec<u32>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        try_parse_lines_as(input, |l| {
+            l.parse::<u32>()
+                .map_err(|_| format!("'{}' is not a valid measurement", l))
+        })
+    }
+
+    fn part1(&self, measurements: &Self::Input) -> Self::Answer1 {
+        find_num_of_increases(measurements)
+    }
+
+    fn part2(&self, measurements: &Self::Input) -> Self::Answer2 {
+        find_num_of_increases_sliding_window(measurements)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+199
+200
+208
+210
+200
+207
+240
+269
+260
+263
+";
+
+    #[test]
+    fn example_part1() {
+        let input = Day1.parse(EXAMPLE).unwrap();
+        assert_eq!(Day1.part1(&input), 7);
+    }
+
+    #[test]
+    fn example_part2() {
+        let input = Day1.parse(EXAMPLE).unwrap();
+        assert_eq!(Day1.part2(&input), 5);
+    }
+
+    #[test]
+    fn parse_reports_line_of_bad_measurement() {
+        let e = Day1.parse("199\n2x0\n").unwrap_err();
+        assert_eq!(e.to_string(), "line 2: '2x0' is not a valid measurement");
+    }
 }
@@ -1,6 +1,8 @@
-use advent_of_code_2021::read_file_lines;
+use crate::generate::InputRng;
+use crate::{try_parse_lines_as, Error, LineError, Solution};
 use itertools::Itertools;
 use phf::phf_map;
+use rand::{seq::SliceRandom, Rng};
 
 const ERROR_SCORES: phf::Map<char, u32> = phf_map! {
     ')' => 3,
@@ -46,8 +48,8 @@ fn check_line(s: &str) -> Result<Vec<char>, char> {
         if is_opener(&c) {
             chunks.push(map_opening_to_closing(&c));
         } else {
-            let expected_char = chunks.pop().unwrap();
-            if c != expected_char {
+            // a closing character without any open chunk is corrupted as well
+            if chunks.pop() != Some(c) {
                 return Err(c);
             }
         }
@@ -55,6 +57,20 @@ fn check_line(s: &str) -> Result<Vec<char>, char> {
     Ok(chunks)
 }
 
+fn parse_line(s: &str) -> Result<String, LineError> {
+    match s
+        .chars()
+        .enumerate()
+        .find(|(_, c)| !is_opener(c) && !ERROR_SCORES.contains_key(c))
+    {
+        Some((i, c)) => Err(LineError::at(
+            i + 1,
+            format!("'{}' is not a chunk character", c),
+        )),
+        None => Ok(s.to_string()),
+    }
+}
+
 fn calculate_syntax_error_score(lines: &Vec<String>) -> u32 {
     lines.iter().fold(0, |score, line| {
         if let Some(c) = check_line(line).err() {
@@ -82,12 +98,115 @@ fn calculate_completion_score(lines: &Vec<String>) -> u64 {
         .unwrap()
 }
 
-fn main() {
-    let lines = read_file_lines("input/day10.txt");
+/// Generates `size` lines of chunks, each either corrupted or incomplete; the first one is always
+/// incomplete, as the completion score needs a middle score.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    const OPENERS: [char; 4] = ['(', '[', '{', '<'];
+
+    (0..size)
+        .map(|i| {
+            let corrupt = i > 0 && rng.gen_bool(0.5);
+            let length = rng.gen_range(40..110);
+            let corrupt_at = rng.gen_range(0..length);
+            let mut corrupted = false;
+            let mut open = Vec::new();
+            let mut line = String::new();
+            for position in 0..length {
+                match open.last() {
+                    Some(&top) if corrupt && !corrupted && position >= corrupt_at => {
+                        let top_index = OPENERS.iter().position(|&c| c == top).unwrap();
+                        let wrong = OPENERS[(top_index + rng.gen_range(1..4)) % 4];
+                        line.push(map_opening_to_closing(&wrong));
+                        corrupted = true;
+                    }
+                    Some(_) if rng.gen_bool(0.45) => {
+                        line.push(map_opening_to_closing(&open.pop().unwrap()));
+                    }
+                    _ => {
+                        let opener = *OPENERS.choose(rng).unwrap();
+                        open.push(opener);
+                        line.push(opener);
+                    }
+                }
+            }
+            if !corrupted && open.is_empty() {
+                line.push(*OPENERS.choose(rng).unwrap());
+            }
+            line.push('\n');
+            line
+        })
+        .collect()
+}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Input = Vec<String>;
+    type Answer1 = u32;
+    type Answer2 = u64;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        try_parse_lines_as(input, parse_line)
+    }
+
+    fn part1(&self, lines: &Self::Input) -> Self::Answer1 {
+        calculate_syntax_error_score(lines)
+    }
+
+    fn part2(&self, lines: &Self::Input) -> Self::Answer2 {
+        calculate_completion_score(lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let syntax_error_score = calculate_syntax_error_score(&lines);
-    println!("The syntax error score is {}", syntax_error_score);
+    const EXAMPLE: &str = "\
+[({(<(())[]>[[{[]{<()<>>
+[(()[<>])]({[<{<<[]>>(
+{([(<{}[<>[]}>{[]{[(<()>
+(((({<>}<{<{<>}{[]{[]{}
+[[<[([]))<([[{}[[()]]]
+[{[{({}]{}}([{[{{{}}([]
+{<[[]]>}<{[{[{[]{()[[[]
+[<(<(<(<{}))><([]([]()
+<{([([[(<>()){}]>(<<{{
+<{([{{}}[<[[[<>{}]]]>[]]
+";
 
-    let completion_score = calculate_completion_score(&lines);
-    println!("The completion score is {}", completion_score);
+    #[test]
+    fn example_part1() {
+        let input = Day10.parse(EXAMPLE).unwrap();
+        assert_eq!(Day10.part1(&input), 26397);
+    }
+
+    #[test]
+    fn example_part2() {
+        let input = Day10.parse(EXAMPLE).unwrap();
+        assert_eq!(Day10.part2(&input), 288957);
+    }
+
+    #[test]
+    fn check_line_finds_first_illegal_character() {
+        assert_eq!(check_line("{([(<{}[<>[]}>{[]{[(<()>"), Err('}'));
+        assert_eq!(check_line("[[<[([]))<([[{}[[()]]]"), Err(')'));
+        assert_eq!(check_line(")"), Err(')'));
+    }
+
+    #[test]
+    fn check_line_returns_missing_closing_characters() {
+        // the characters still to close are kept as a stack, so the last one is closed first
+        let expected = "}}]])})]".chars().rev().collect();
+        assert_eq!(check_line("[({(<(())[]>[[{[]{<()<>>"), Ok(expected));
+        assert_eq!(check_line("()"), Ok(vec![]));
+    }
+
+    #[test]
+    fn completion_score() {
+        assert_eq!(
+            calc_completion_score_for("}}]])})]".chars().rev().collect()),
+            288957
+        );
+    }
 }
@@ -1,27 +1,29 @@
-use advent_of_code_2021::{
-    parse_2d_number_grid, print_2d_array, print_u8_2d_array_with_delim, read_file_to_string,
+use crate::generate::{generate_digit_grid, InputRng};
+use crate::{
+    debug, format_u8_2d_array_with_delim,
+    render::{Animation, Frame, Palette},
+    trace, Coords, Error, Grid, Param, Params, Solution,
 };
-use array2d::Array2D;
 
 #[derive(Clone)]
-struct OctopusGrid {
-    tiles: Array2D<u8>,
+pub struct OctopusGrid {
+    tiles: Grid<u8>,
     num_of_flashes: u32,
 }
 
 impl OctopusGrid {
-    pub fn parse(s: &str) -> Self {
-        Self {
-            tiles: parse_2d_number_grid(s),
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        Ok(Self {
+            tiles: Grid::parse_digits(s)?,
             num_of_flashes: 0,
-        }
+        })
     }
 
     pub fn simulate_until_all_flash(&mut self) -> u32 {
         let mut step = 1;
         loop {
             self.step();
-            print_2d_array(&self.tiles);
+            debug!("Step {}:\n{}", step, self.tiles);
             if self.did_every_octopus_flash() {
                 return step;
             }
@@ -30,12 +32,10 @@ impl OctopusGrid {
     }
 
     pub fn simulate(&mut self, num_of_steps: u32) -> u32 {
-        println!("Original state:");
-        print_2d_array(&self.tiles);
+        debug!("Original state:\n{}", self.tiles);
         for step in 1..=num_of_steps {
             self.step();
-            println!("Step {}:", step);
-            print_2d_array(&self.tiles);
+            debug!("Step {}:\n{}", step, self.tiles);
         }
 
         self.num_of_flashes
@@ -47,89 +47,147 @@ impl OctopusGrid {
     }
 
     fn increment_energy_levels(&mut self) {
-        for tile in self.tiles.elements_row_major_iter_mut() {
+        for tile in self.tiles.values_mut() {
             *tile += 1;
         }
     }
 
-    fn flash_tiles_in_positions(&mut self, tiles_to_flash: Vec<(usize, usize)>) {
-        print_u8_2d_array_with_delim(&self.tiles);
+    fn flash_tiles_in_positions(&mut self, tiles_to_flash: Vec<Coords>) {
+        trace!("{}", format_u8_2d_array_with_delim(self.tiles.as_array()));
         if tiles_to_flash.is_empty() {
             return;
         }
 
-        for (x, y) in tiles_to_flash {
-            self.flash(x, y);
+        for coords in tiles_to_flash {
+            self.flash(coords);
         }
 
         self.flash_tiles_in_positions(self.gather_tiles_to_be_flashed());
     }
 
-    fn gather_tiles_to_be_flashed(&self) -> Vec<(usize, usize)> {
-        let mut tiles_to_flash = Vec::new();
-        for (y, x) in self.tiles.indices_row_major() {
-            if *self.tiles.get(y, x).unwrap() > 9 {
-                tiles_to_flash.push((x, y));
-            }
-        }
-
-        tiles_to_flash
+    fn gather_tiles_to_be_flashed(&self) -> Vec<Coords> {
+        self.tiles
+            .iter()
+            .filter(|&(_, &energy)| energy > 9)
+            .map(|(coords, _)| coords)
+            .collect()
     }
 
-    fn flash(&mut self, x: usize, y: usize) {
+    fn flash(&mut self, coords: Coords) {
         self.num_of_flashes += 1;
 
-        self.tiles.set(y, x, 0).unwrap();
-
-        let x = x as i32;
-        let y = y as i32;
-        let neighbour_positions = [
-            (x - 1, y - 1),
-            (x, y - 1),
-            (x + 1, y - 1),
-            (x - 1, y),
-            (x + 1, y),
-            (x - 1, y + 1),
-            (x, y + 1),
-            (x + 1, y + 1),
-        ];
-
-        let neighbour_positions_filtered = neighbour_positions.iter().filter_map(|(x, y)| {
-            if *x >= 0 && *y >= 0 {
-                Some((*x as usize, *y as usize))
-            } else {
-                None
-            }
-        });
+        self.tiles.set(coords, 0);
 
-        for (x, y) in neighbour_positions_filtered {
-            if let Some(element) = self.tiles.get_mut(y, x) {
-                if *element > 0 {
-                    *element += 1;
-                }
+        let neighbours = self.tiles.neighbours8(coords).collect::<Vec<_>>();
+        for neighbour in neighbours {
+            let energy = &mut self.tiles[neighbour];
+            if *energy > 0 {
+                *energy += 1;
             }
         }
     }
 
     fn did_every_octopus_flash(&self) -> bool {
-        self.tiles.elements_row_major_iter().all(|&o| o == 0)
+        self.tiles.values().all(|&o| o == 0)
     }
 }
 
-fn main() {
-    let input = read_file_to_string("input/day11.txt");
+/// Renders every step until all octopuses flash at once, with those that just flashed in white and
+/// the others brighter the more energy they have.
+pub fn animate(grid: &OctopusGrid) -> Animation {
+    let mut grid = grid.clone();
+    let render = |grid: &OctopusGrid| Frame::from_grid(&grid.tiles, |&energy| energy);
 
-    let mut grid = OctopusGrid::parse(&input);
+    let mut frames = vec![render(&grid)];
+    while !grid.did_every_octopus_flash() {
+        grid.step();
+        frames.push(render(&grid));
+    }
 
-    let num_of_flashes = grid.clone().simulate(100);
-    println!(
-        "The number of flashes after 100 steps is {}",
-        num_of_flashes
+    let energy_levels = Palette::gradient([10, 10, 40], [60, 120, 200], 9);
+    let palette = Palette::new(
+        std::iter::once([255, 255, 255])
+            .chain(energy_levels.colours().iter().copied())
+            .collect(),
     );
 
-    let step_when_all_octopuses_flash = grid.simulate_until_all_flash();
-    println!(
-        "The step when all octopuses flash is step {}",
-        step_when_all_octopuses_flash
-    );
+    Animation {
+        frames,
+        palette,
+        scale: 16,
+    }
+}
+
+/// Generates a `size`x`size` grid of octopus energy levels.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    generate_digit_grid(rng, size, 1..=8)
+}
+
+pub const STEPS: Param = Param {
+    name: "steps",
+    default: 100,
+    description: "the number of steps to count the flashes of",
+    check: |steps| Param::in_range(steps, 0..=u32::MAX as usize),
+};
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Input = OctopusGrid;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    const PARAMS: &'static [Param] = &[STEPS];
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        OctopusGrid::parse(input)
+    }
+
+    fn part1(&self, grid: &Self::Input) -> Self::Answer1 {
+        self.part1_with(grid, &Params::default())
+    }
+
+    fn part2(&self, grid: &Self::Input) -> Self::Answer2 {
+        grid.clone().simulate_until_all_flash()
+    }
+
+    fn part1_with(&self, grid: &Self::Input, params: &Params) -> Self::Answer1 {
+        grid.clone().simulate(params.get(&STEPS) as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+5483143223
+2745854711
+5264556173
+6141336146
+6357385478
+4167524645
+2176841721
+6882881134
+4846848554
+5283751526
+";
+
+    #[test]
+    fn example_part1() {
+        let input = Day11.parse(EXAMPLE).unwrap();
+        assert_eq!(Day11.part1(&input), 1656);
+    }
+
+    #[test]
+    fn example_part2() {
+        let input = Day11.parse(EXAMPLE).unwrap();
+        assert_eq!(Day11.part2(&input), 195);
+    }
+
+    #[test]
+    fn flashes_after_10_steps() {
+        let mut grid = OctopusGrid::parse(EXAMPLE).unwrap();
+        assert_eq!(grid.simulate(10), 204);
+    }
 }
@@ -1,10 +1,12 @@
-use advent_of_code_2021::read_file_to_string;
+use crate::generate::InputRng;
+use crate::{trace, Error, LineError, Solution};
 use itertools::Itertools;
+use rand::{seq::SliceRandom, Rng};
 use std::collections::HashSet;
 use std::str::FromStr;
 
 #[derive(Eq, PartialEq, Hash, Debug)]
-enum NodeType {
+pub enum NodeType {
     Big,
     Small,
     Start,
@@ -12,15 +14,18 @@ enum NodeType {
 }
 
 #[derive(Eq, PartialEq, Hash, Debug)]
-struct Node {
+pub struct Node {
     name: String,
     node_type: NodeType,
 }
 
 impl FromStr for Node {
-    type Err = ();
+    type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || !s.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(format!("'{}' is not a valid cave name", s));
+        }
         let cave_type = if s == "start" {
             NodeType::Start
         } else if s == "end" {
@@ -39,7 +44,7 @@ impl FromStr for Node {
 }
 
 #[derive(Eq, PartialEq, Hash)]
-struct Edge {
+pub struct Edge {
     u: String,
     v: String,
 }
@@ -91,27 +96,39 @@ fn can_step_on_node(node: &Node, path: &Vec<&Node>, node_visitable_twice: &Optio
     };
 }
 
-struct CaveSystem {
+pub struct CaveSystem {
     nodes: HashSet<Node>,
     edges: HashSet<Edge>,
 }
 
 impl CaveSystem {
-    pub fn parse(s: &str) -> Self {
+    pub fn parse(s: &str) -> Result<Self, Error> {
         let mut nodes = HashSet::new();
         let mut edges = HashSet::new();
-        for line in s.lines() {
-            let (u_str, v_str) = line.split_once('-').unwrap();
-            let u = Node::from_str(u_str).unwrap();
-            let v = Node::from_str(v_str).unwrap();
+        for (i, line) in s.lines().enumerate() {
+            let (u, v) = Self::parse_connection(line).map_err(|e| Error::malformed(i + 1, e))?;
+            edges.insert(Edge::new(&u.name, &v.name));
             nodes.insert(u);
             nodes.insert(v);
+        }
 
-            let edge = Edge::new(u_str, v_str);
-            edges.insert(edge);
+        for name in ["start", "end"] {
+            if !nodes.iter().any(|n| n.name == name) {
+                return Err(Error::Invalid(format!("there is no '{}' cave", name)));
+            }
         }
 
-        Self { nodes, edges }
+        Ok(Self { nodes, edges })
+    }
+
+    fn parse_connection(line: &str) -> Result<(Node, Node), LineError> {
+        let (u_str, v_str) = line
+            .split_once('-')
+            .ok_or_else(|| format!("'{}' is not a connection of the form 'a-b'", line))?;
+        let u = Node::from_str(u_str).map_err(|e| LineError::at(1, e))?;
+        let v = Node::from_str(v_str).map_err(|e| LineError::at(u_str.chars().count() + 2, e))?;
+
+        Ok((u, v))
     }
 
     pub fn find_num_of_all_paths_small_caves_count_once(&self) -> u32 {
@@ -151,7 +168,7 @@ impl CaveSystem {
         path.push(node);
         if node.node_type == NodeType::End {
             let path_string = format_path(&path);
-            println!("Found a path: {}", &path_string);
+            trace!("Found a path: {}", &path_string);
             found_paths.insert(path_string);
             return;
         }
@@ -174,19 +191,116 @@ impl CaveSystem {
     }
 }
 
-fn main() {
-    let input = read_file_to_string("input/day12.txt");
-    let system = CaveSystem::parse(&input);
-    let num_of_paths_small_caves_count_once = system.find_num_of_all_paths_small_caves_count_once();
-    println!(
-        "The number of all paths found when small caves count only once is {}",
-        num_of_paths_small_caves_count_once
-    );
-
-    let num_of_paths_one_small_cave_counts_twice =
-        system.find_num_of_all_paths_one_small_cave_counts_twice();
-    println!(
-        "The number of all paths found when one small cave counts twice is {}",
-        num_of_paths_one_small_cave_counts_twice
-    );
+/// Generates a cave system of `size` small caves, but at most 676 as they are named with two
+/// letters, and a big cave for every four small ones. Big caves are never connected to each other,
+/// as there would be infinitely many paths otherwise.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    let size = size.min(26 * 26);
+    let name = |i: usize| {
+        format!(
+            "{}{}",
+            (b'a' + (i / 26) as u8) as char,
+            (b'a' + (i % 26) as u8) as char
+        )
+    };
+    let small = (0..size).map(name).collect::<Vec<_>>();
+    let big = (0..(size / 4).max(1))
+        .map(|i| name(i).to_uppercase())
+        .collect::<Vec<_>>();
+    let caves = small.iter().chain(&big).collect::<Vec<_>>();
+
+    let mut connections = Vec::new();
+    for &cave in &caves {
+        for _ in 0..rng.gen_range(1..=2) {
+            let other = *caves.choose(rng).unwrap();
+            let both_big = big.contains(cave) && big.contains(other);
+            let known =
+                connections.contains(&(cave, other)) || connections.contains(&(other, cave));
+            if other != cave && !both_big && !known {
+                connections.push((cave, other));
+            }
+        }
+    }
+
+    let mut lines = connections
+        .into_iter()
+        .map(|(u, v)| format!("{}-{}\n", u, v))
+        .collect::<Vec<_>>();
+    for end in ["start", "end"] {
+        for cave in caves.choose_multiple(rng, 2) {
+            lines.push(format!("{}-{}\n", end, cave));
+        }
+    }
+    lines.shuffle(rng);
+
+    lines.concat()
+}
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    type Input = CaveSystem;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        CaveSystem::parse(input)
+    }
+
+    fn part1(&self, system: &Self::Input) -> Self::Answer1 {
+        system.find_num_of_all_paths_small_caves_count_once()
+    }
+
+    fn part2(&self, system: &Self::Input) -> Self::Answer2 {
+        system.find_num_of_all_paths_one_small_cave_counts_twice()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMALL_EXAMPLE: &str = "\
+start-A
+start-b
+A-c
+A-b
+b-d
+A-end
+b-end
+";
+
+    const LARGER_EXAMPLE: &str = "\
+dc-end
+HN-start
+start-kj
+dc-start
+dc-HN
+LN-dc
+HN-end
+kj-sa
+kj-HN
+kj-dc
+";
+
+    #[test]
+    fn example_part1() {
+        let input = Day12.parse(SMALL_EXAMPLE).unwrap();
+        assert_eq!(Day12.part1(&input), 10);
+        let input = Day12.parse(LARGER_EXAMPLE).unwrap();
+        assert_eq!(Day12.part1(&input), 19);
+    }
+
+    #[test]
+    fn example_part2() {
+        let input = Day12.parse(SMALL_EXAMPLE).unwrap();
+        assert_eq!(Day12.part2(&input), 36);
+        let input = Day12.parse(LARGER_EXAMPLE).unwrap();
+        assert_eq!(Day12.part2(&input), 103);
+    }
+
+    #[test]
+    fn parse_requires_start_and_end() {
+        assert!(Day12.parse("start-A\nA-b\n").is_err());
+    }
 }
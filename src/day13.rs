@@ -1,10 +1,17 @@
-use advent_of_code_2021::read_file_to_string;
+use crate::generate::InputRng;
+use crate::{
+    debug,
+    render::{Animation, Frame, Palette},
+    split_blocks, Error, LineError, Solution,
+};
 use array2d::Array2D;
+use rand::{seq::SliceRandom, Rng};
 use sscanf::scanf;
+use std::collections::HashSet;
 use std::str::FromStr;
 
 #[derive(Debug)]
-enum Direction {
+pub enum Direction {
     Up,
     Left,
 }
@@ -27,7 +34,7 @@ impl std::fmt::Display for Direction {
     }
 }
 
-struct Fold {
+pub struct Fold {
     along: usize,
     direction: Direction,
 }
@@ -56,28 +63,16 @@ impl std::fmt::Display for Fold {
     }
 }
 
-struct Paper {
+pub struct Paper {
     tiles: Array2D<bool>,
 }
 
 impl Paper {
-    pub fn parse(dots_str: &[&str]) -> Self {
-        let dots = dots_str
-            .iter()
-            .filter_map(|l| {
-                l.split_once(',').map(|(x_str, y_str)| {
-                    (
-                        x_str.parse::<usize>().unwrap(),
-                        (y_str.parse::<usize>().unwrap()),
-                    )
-                })
-            })
-            .collect::<Vec<_>>();
-
-        let (width, height) = Self::get_paper_size(&dots);
+    pub fn from_dots(dots: &[(usize, usize)]) -> Self {
+        let (width, height) = Self::get_paper_size(dots);
 
         let mut tiles = Array2D::filled_with(false, height, width);
-        for (x, y) in dots {
+        for &(x, y) in dots {
             tiles.set(y, x, true).unwrap();
         }
 
@@ -146,7 +141,13 @@ impl Paper {
         Self { tiles }
     }
 
-    fn get_paper_size(dots: &Vec<(usize, usize)>) -> (usize, usize) {
+    pub fn describe_size(&self) -> String {
+        let width = self.tiles.num_columns();
+        let height = self.tiles.num_rows();
+        format!("The paper is {}x{}:", width, height)
+    }
+
+    fn get_paper_size(dots: &[(usize, usize)]) -> (usize, usize) {
         let (highest_x, highest_y) = dots.iter().fold((0, 0), |(c_x, c_y), (d_x, d_y)| {
             (c_x.max(*d_x), c_y.max(*d_y))
         });
@@ -157,9 +158,6 @@ impl Paper {
 
 impl std::fmt::Display for Paper {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let width = self.tiles.num_columns();
-        let height = self.tiles.num_rows();
-        writeln!(f, "The paper is {}x{}:", width, height)?;
         for row in self.tiles.rows_iter() {
             for column in row.into_iter() {
                 let c = if *column { '#' } else { '.' };
@@ -171,43 +169,219 @@ impl std::fmt::Display for Paper {
     }
 }
 
-fn calculate_number_of_dots_after_one_fold(dots_str: &[&str], fold: &Fold) -> u32 {
-    let mut paper = Paper::parse(dots_str);
+fn parse_dot(s: &str) -> Result<(usize, usize), LineError> {
+    let (x_str, y_str) = s
+        .split_once(',')
+        .ok_or_else(|| format!("'{}' is not a dot of the form 'x,y'", s))?;
+    let x = x_str
+        .parse::<usize>()
+        .map_err(|_| LineError::at(1, format!("'{}' is not a valid coordinate", x_str)))?;
+    let y = y_str.parse::<usize>().map_err(|_| {
+        LineError::at(
+            x_str.len() + 2,
+            format!("'{}' is not a valid coordinate", y_str),
+        )
+    })?;
+
+    Ok((x, y))
+}
+
+fn calculate_number_of_dots_after_one_fold(dots: &[(usize, usize)], fold: &Fold) -> u32 {
+    let mut paper = Paper::from_dots(dots);
     paper = paper.fold_by(fold);
 
     paper.count_num_of_dots()
 }
 
-fn fold_paper_according_to_instructions(dots_str: &[&str], folds: &[Fold]) {
-    let mut paper = Paper::parse(dots_str);
+fn fold_paper_according_to_instructions(dots: &[(usize, usize)], folds: &[Fold]) -> Paper {
+    let mut paper = Paper::from_dots(dots);
 
-    println!("Initial paper state:");
-    println!("{}", &paper);
+    debug!(
+        "Initial paper state:\n{}\n{}",
+        paper.describe_size(),
+        &paper
+    );
 
     for fold in folds {
         paper = paper.fold_by(fold);
-        println!("Paper after folding:");
-        println!("{}", &paper);
+        debug!(
+            "Paper after folding {}:\n{}\n{}",
+            fold,
+            paper.describe_size(),
+            &paper
+        );
     }
+
+    paper
 }
 
-fn main() {
-    let input = read_file_to_string("input/day13.txt");
+/// Renders the paper before and after every fold.
+pub fn animate(dots: &[(usize, usize)], folds: &[Fold]) -> Animation {
+    let render = |paper: &Paper| {
+        Frame::from_fn(
+            paper.tiles.num_columns(),
+            paper.tiles.num_rows(),
+            |(x, y)| *paper.tiles.get(y, x).unwrap() as u8,
+        )
+    };
+
+    let mut paper = Paper::from_dots(dots);
+    let mut frames = vec![render(&paper)];
+    for fold in folds {
+        paper = paper.fold_by(fold);
+        frames.push(render(&paper));
+    }
 
-    let (dots_str, folds_str): (Vec<_>, Vec<_>) = input.lines().partition(|l| !l.contains("fold"));
+    Animation {
+        frames,
+        palette: Palette::new(vec![[20, 20, 20], [255, 230, 120]]),
+        scale: 1,
+    }
+}
+
+/// Generates `size` dots on a sheet that folds down to 40x6 along 5 vertical and 7 horizontal
+/// lines, like the real one. Every fold is exactly through the middle of the paper and no dot lies
+/// on a fold line.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    let mut directions = [vec!['x'; 5], vec!['y'; 7]].concat();
+    directions.shuffle(rng);
+
+    // unfold the final paper, doubling it with every fold undone
+    let (mut width, mut height) = (40, 6);
+    let mut folds = Vec::new();
+    for &direction in directions.iter().rev() {
+        let along = if direction == 'x' {
+            &mut width
+        } else {
+            &mut height
+        };
+        folds.push((direction, *along));
+        *along = 2 * *along + 1;
+    }
+    folds.reverse();
+
+    // the corner ends up at the largest coordinates when mirrored across every fold, which gives
+    // the paper its full size
+    let mut dots = vec![(width - 1, height - 1)];
+    let mut seen = dots.iter().copied().collect::<HashSet<_>>();
+    while seen.len() < size.min(width * height) {
+        let (mut x, mut y) = (rng.gen_range(0..40), rng.gen_range(0..6));
+        for &(direction, along) in folds.iter().rev() {
+            let coordinate = if direction == 'x' { &mut x } else { &mut y };
+            if rng.gen_bool(0.5) {
+                *coordinate = 2 * along - *coordinate;
+            }
+        }
+        if seen.insert((x, y)) {
+            dots.push((x, y));
+        }
+    }
+    dots.shuffle(rng);
 
-    let folds = folds_str
+    let dots = dots.iter().map(|(x, y)| format!("{},{}\n", x, y));
+    let folds = folds
         .iter()
-        .map(|l| Fold::from_str(l).unwrap())
-        .collect::<Vec<_>>();
+        .map(|(direction, along)| format!("fold along {}={}\n", direction, along));
+    format!(
+        "{}\n{}",
+        dots.collect::<String>(),
+        folds.collect::<String>()
+    )
+}
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    type Input = (Vec<(usize, usize)>, Vec<Fold>);
+    type Answer1 = u32;
+    type Answer2 = Paper;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        let blocks = split_blocks(input);
+        let (dots_block, folds_block) = match &blocks[..] {
+            [dots, folds] => (dots, folds),
+            [] => return Err(Error::EmptyInput),
+            [_] => return Err(Error::Invalid("there are no fold instructions".to_string())),
+            [_, _, extra, ..] => {
+                return Err(Error::malformed(
+                    extra.first_line,
+                    "expected nothing after the fold instructions",
+                ))
+            }
+        };
 
-    let num_of_dots_after_one_fold =
-        calculate_number_of_dots_after_one_fold(&dots_str, folds.first().unwrap());
+        let dots = dots_block
+            .numbered_lines()
+            .map(|(line, s)| parse_dot(s).map_err(|e| Error::malformed(line, e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let folds = folds_block
+            .numbered_lines()
+            .map(|(line, s)| Fold::from_str(s).map_err(|e| Error::malformed(line, e)))
+            .collect::<Result<Vec<_>, _>>()?;
 
-    println!(
-        "The number of dots after one fold is {}",
-        num_of_dots_after_one_fold
-    );
+        Ok((dots, folds))
+    }
 
-    fold_paper_according_to_instructions(&dots_str, &folds);
+    fn part1(&self, (dots, folds): &Self::Input) -> Self::Answer1 {
+        calculate_number_of_dots_after_one_fold(dots, folds.first().unwrap())
+    }
+
+    fn part2(&self, (dots, folds): &Self::Input) -> Self::Answer2 {
+        fold_paper_according_to_instructions(dots, folds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+6,10
+0,14
+9,10
+0,3
+10,4
+4,11
+6,0
+6,12
+4,1
+0,13
+10,12
+3,4
+3,0
+8,4
+1,10
+2,14
+8,10
+9,0
+
+fold along y=7
+fold along x=5
+";
+
+    #[test]
+    fn example_part1() {
+        let input = Day13.parse(EXAMPLE).unwrap();
+        assert_eq!(Day13.part1(&input), 17);
+    }
+
+    #[test]
+    fn example_part2() {
+        let input = Day13.parse(EXAMPLE).unwrap();
+        let expected = "\
+#####
+#...#
+#...#
+#...#
+#####
+.....
+.....
+";
+        assert_eq!(Day13.part2(&input).to_string(), expected);
+    }
+
+    #[test]
+    fn parse_requires_folds() {
+        assert!(Day13.parse("6,10\n0,14\n").is_err());
+    }
 }
@@ -1,12 +1,14 @@
-use advent_of_code_2021::read_file_lines_extract_first;
+use crate::generate::InputRng;
+use crate::{debug, trace, Error, LineError, Param, Params, Solution};
 use itertools::{
     FoldWhile::{Continue, Done},
     Itertools, MinMaxResult,
 };
+use rand::seq::SliceRandom;
 use sscanf::scanf;
 use std::{collections::HashMap, hash::Hash, str::FromStr};
 
-struct RuleSlow {
+pub struct RuleSlow {
     pattern_1: char,
     pattern_2: char,
     insert: char,
@@ -62,7 +64,7 @@ fn char_frequencies_of(s: &str) -> HashMap<char, usize> {
 }
 
 #[derive(Clone)]
-struct PolymerSlow {
+pub struct PolymerSlow {
     elements: String,
 }
 
@@ -81,13 +83,11 @@ impl PolymerSlow {
     }
 
     pub fn apply_rules_n_times(self, rules: &[RuleSlow], n: usize) -> Self {
-        println!("Original:");
-        println!("{}", self);
+        trace!("Original:\n{}", self);
         let mut this = self;
         for step in 1..=n {
-            println!("Step {}:", step);
             this = this.apply_rules(rules);
-            println!("{}", this);
+            trace!("Step {}:\n{}", step, this);
         }
 
         this
@@ -133,26 +133,23 @@ impl std::fmt::Display for PolymerSlow {
     }
 }
 
-fn calculate_most_common_minus_least_common_elements_after_10_steps_slow(
+fn calculate_most_common_minus_least_common_elements_slow(
     polymer: String,
     rules: &[String],
-) {
+    num_of_steps: usize,
+) -> usize {
     let polymer = PolymerSlow::new(polymer);
     let rules = rules
         .iter()
         .map(|s| RuleSlow::from_str(s).unwrap())
         .collect::<Vec<_>>();
 
-    let result = polymer.apply_rules_n_times(&rules, 10);
-    let value = result.most_common_element_minus_least_common_element();
-    println!(
-        "The quantity of the most common element minus the least common element after 10 steps is {}",
-        value
-    );
+    let result = polymer.apply_rules_n_times(&rules, num_of_steps);
+    result.most_common_element_minus_least_common_element()
 }
 
 #[derive(Debug)]
-struct RuleFast {
+pub struct RuleFast {
     p1: char,
     p2: char,
     new: char,
@@ -171,14 +168,15 @@ impl RuleFast {
 impl FromStr for RuleFast {
     type Err = String;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (p1, p2, new) = scanf!(s, "{}{} -> {}", char, char, char).ok_or("Invalid format")?;
+        let (p1, p2, new) = scanf!(s, "{}{} -> {}", char, char, char)
+            .ok_or_else(|| format!("'{}' is not a rule of the form 'AB -> C'", s))?;
 
         Ok(Self { p1, p2, new })
     }
 }
 
 #[derive(Debug)]
-struct PolymerFast {
+pub struct PolymerFast {
     active_rules: HashMap<(char, char), usize>,
     element_frequencies: HashMap<char, usize>,
 }
@@ -211,9 +209,8 @@ impl PolymerFast {
 
     pub fn apply_rules_n_times(&mut self, rules: &[RuleFast], n: usize) {
         for step in 1..=n {
-            println!("Step {}...", step);
             self.apply_rules(rules);
-            println!("Step {} complete.", step);
+            debug!("Step {} complete", step);
         }
     }
 
@@ -235,27 +232,194 @@ impl PolymerFast {
     }
 }
 
-fn calculate_most_common_minus_least_common_elements_after_40_steps_fast(
+fn calculate_most_common_minus_least_common_elements_fast(
     polymer: &str,
     rules: &[String],
-) {
+    num_of_steps: usize,
+) -> usize {
     let mut polymer = PolymerFast::parse(polymer);
     let rules = rules
         .iter()
         .map(|s| RuleFast::from_str(s).unwrap())
         .collect::<Vec<_>>();
 
-    polymer.apply_rules_n_times(&rules, 40);
+    polymer.apply_rules_n_times(&rules, num_of_steps);
+
+    polymer.most_common_element_minus_least_common_element()
+}
+
+/// Generates a template of `size` elements and a rule for every pair of 10 elements.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    const ELEMENTS: [char; 10] = ['B', 'C', 'F', 'H', 'K', 'N', 'O', 'P', 'S', 'V'];
+
+    let template = (0..size.max(1))
+        .map(|_| *ELEMENTS.choose(rng).unwrap())
+        .collect::<String>();
+    let mut rules = ELEMENTS
+        .iter()
+        .cartesian_product(ELEMENTS.iter())
+        .map(|(a, b)| format!("{}{} -> {}\n", a, b, ELEMENTS.choose(rng).unwrap()))
+        .collect::<Vec<_>>();
+    rules.shuffle(rng);
 
-    let value = polymer.most_common_element_minus_least_common_element();
-    println!(
-        "The quantity of the most common element minus the least common element after 40 steps is {}",
-        value
-    );
+    format!("{}\n\n{}", template, rules.concat())
 }
 
-fn main() {
-    let (polymer, rules) = read_file_lines_extract_first("input/day14.txt");
-    calculate_most_common_minus_least_common_elements_after_10_steps_slow(polymer.clone(), &rules);
-    calculate_most_common_minus_least_common_elements_after_40_steps_fast(&polymer, &rules);
+/// The polymer string doubles in length with every step, and inserting into it takes seconds by
+/// this many steps.
+const MAX_SLOW_STEPS: usize = 15;
+
+/// The counts of element pairs double with every step too, and overflow a `usize` not many steps
+/// after this one.
+const MAX_FAST_STEPS: usize = 50;
+
+pub const PART1_STEPS: Param = Param {
+    name: "part1_steps",
+    default: 10,
+    description: "the insertion steps of the first part, done on the whole polymer string",
+    check: |steps| Param::in_range(steps, 0..=MAX_SLOW_STEPS),
+};
+
+pub const PART2_STEPS: Param = Param {
+    name: "part2_steps",
+    default: 40,
+    description: "the insertion steps of the second part, done on counts of element pairs",
+    check: |steps| Param::in_range(steps, 0..=MAX_FAST_STEPS),
+};
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Input = (String, Vec<String>);
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    const PARAMS: &'static [Param] = &[PART1_STEPS, PART2_STEPS];
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        let mut lines = input.lines().enumerate().filter(|(_, l)| !l.is_empty());
+        let (_, polymer) = lines.next().ok_or(Error::EmptyInput)?;
+        if let Some(i) = polymer.chars().position(|c| !c.is_ascii_uppercase()) {
+            return Err(Error::malformed(
+                1,
+                LineError::at(i + 1, "expected an element"),
+            ));
+        }
+
+        // rules are validated here so solving the parts never has to deal with a bad one
+        let rules = lines
+            .map(|(i, l)| {
+                RuleFast::from_str(l)
+                    .map(|_| l.to_string())
+                    .map_err(|e| Error::malformed(i + 1, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok((polymer.to_string(), rules))
+    }
+
+    fn part1(&self, input: &Self::Input) -> Self::Answer1 {
+        self.part1_with(input, &Params::default())
+    }
+
+    fn part2(&self, input: &Self::Input) -> Self::Answer2 {
+        self.part2_with(input, &Params::default())
+    }
+
+    fn part1_with(&self, (polymer, rules): &Self::Input, params: &Params) -> Self::Answer1 {
+        calculate_most_common_minus_least_common_elements_slow(
+            polymer.clone(),
+            rules,
+            params.get(&PART1_STEPS),
+        )
+    }
+
+    fn part2_with(&self, (polymer, rules): &Self::Input, params: &Params) -> Self::Answer2 {
+        calculate_most_common_minus_least_common_elements_fast(
+            polymer,
+            rules,
+            params.get(&PART2_STEPS),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+NNCB
+
+CH -> B
+HH -> N
+CB -> H
+NH -> C
+HB -> C
+HC -> B
+HN -> C
+NN -> C
+BH -> H
+NC -> B
+NB -> B
+BN -> B
+BB -> N
+BC -> B
+CC -> N
+CN -> C
+";
+
+    #[test]
+    fn example_part1() {
+        let input = Day14.parse(EXAMPLE).unwrap();
+        assert_eq!(Day14.part1(&input), 1588);
+    }
+
+    #[test]
+    fn example_part2() {
+        let input = Day14.parse(EXAMPLE).unwrap();
+        assert_eq!(Day14.part2(&input), 2188189693529);
+    }
+
+    #[test]
+    fn steps_are_bounded_by_what_each_part_can_take() {
+        assert!(Params::parse(Day14::PARAMS, &["part1_steps=15"]).is_ok());
+        assert!(Params::parse(Day14::PARAMS, &["part1_steps=40"]).is_err());
+        assert!(Params::parse(Day14::PARAMS, &["part2_steps=50"]).is_ok());
+        assert!(Params::parse(Day14::PARAMS, &["part2_steps=100"]).is_err());
+    }
+
+    #[test]
+    fn slow_polymer_after_two_steps() {
+        let (_, rules) = Day14.parse(EXAMPLE).unwrap();
+        let rules = rules
+            .iter()
+            .map(|r| RuleSlow::from_str(r).unwrap())
+            .collect::<Vec<_>>();
+        let polymer = PolymerSlow::new("NNCB".to_string()).apply_rules_n_times(&rules, 2);
+        assert_eq!(polymer.to_string(), "NBCCNBBBCBHCB");
+    }
+
+    proptest! {
+        #[test]
+        fn slow_and_fast_polymers_agree(
+            template in "[A-D]{1,10}",
+            // not every pair needs a rule, those without one are left alone
+            insertions in prop::collection::btree_map(("[A-D]", "[A-D]"), "[A-D]", 0..16),
+            num_of_steps in 0..8usize,
+        ) {
+            let rules = insertions
+                .iter()
+                .map(|((p1, p2), insert)| format!("{}{} -> {}", p1, p2, insert))
+                .collect::<Vec<_>>();
+            let slow_rules = rules.iter().map(|r| RuleSlow::from_str(r).unwrap()).collect::<Vec<_>>();
+            let fast_rules = rules.iter().map(|r| RuleFast::from_str(r).unwrap()).collect::<Vec<_>>();
+
+            let slow = PolymerSlow::new(template.clone()).apply_rules_n_times(&slow_rules, num_of_steps);
+            let mut fast = PolymerFast::parse(&template);
+            fast.apply_rules_n_times(&fast_rules, num_of_steps);
+
+            prop_assert_eq!(char_frequencies_of(&slow.elements), fast.element_frequencies);
+        }
+    }
 }
@@ -1,143 +1,159 @@
-use advent_of_code_2021::{parse_2d_number_grid, read_file_to_string};
-use array2d::Array2D;
-use std::collections::HashSet;
+use crate::generate::{generate_digit_grid, InputRng};
+use crate::search::{astar, Graph};
+use crate::{debug, Coords, Error, Grid, Param, Params, Solution};
 use std::time::Instant;
 
-type Coords = (usize, usize);
-
-struct Map {
-    tiles: Array2D<u8>,
+pub struct Map {
+    tiles: Grid<u8>,
     end: Coords,
 }
 
 impl Map {
-    pub fn parse(s: &str) -> Self {
-        let tiles = parse_2d_number_grid(s);
-        let end = (tiles.num_columns() - 1, tiles.num_rows() - 1);
-        Self { tiles, end }
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        let tiles = Grid::parse_digits(s)?;
+        let end = (tiles.width() - 1, tiles.height() - 1);
+        Ok(Self { tiles, end })
     }
 
-    pub fn parse_whole(s: &str) -> Self {
-        let small = parse_2d_number_grid(s);
-        let small_width = small.num_columns();
-        let small_height = small.num_rows();
-        let width = small_width * 5;
-        let height = small_height * 5;
-        let mut tiles = Array2D::filled_with(0u8, height, width);
-
-        for (row, column) in tiles.indices_row_major() {
-            let t_r = row / small_height;
-            let t_c = column / small_width;
-            let r = row % small_height;
-            let c = column % small_width;
-
-            let value = small.get(r, c).unwrap();
-            let mut value_modified = (*value as usize + t_r + t_c) as u8;
-            if value_modified > 9 {
-                value_modified -= 9;
-            }
-
-            tiles.set(row, column, value_modified).unwrap();
-        }
+    /// Tiles the map `factor` times in both directions, each tile riskier than the last.
+    pub fn expand(&self, factor: usize) -> Self {
+        let small = &self.tiles;
+        let small_width = small.width();
+        let small_height = small.height();
+        let mut tiles = Grid::filled_with(0u8, small_width * factor, small_height * factor);
 
-        let end = (tiles.num_columns() - 1, tiles.num_rows() - 1);
-        Self { tiles, end }
-    }
+        for (x, y) in tiles.coords() {
+            let t_x = x / small_width;
+            let t_y = y / small_height;
 
-    pub fn calculate_lowest_risk_value(&self) -> usize {
-        let mut risk_values_in_tiles =
-            Array2D::filled_with(usize::MAX, self.tiles.num_rows(), self.tiles.num_columns());
-        risk_values_in_tiles.set(0, 0, 0).unwrap();
-        risk_values_in_tiles
-            .set(1, 0, *self.tiles.get(1, 0).unwrap() as usize)
-            .unwrap();
-        risk_values_in_tiles
-            .set(0, 1, *self.tiles.get(0, 1).unwrap() as usize)
-            .unwrap();
-
-        let mut working_with: HashSet<Coords> = HashSet::new();
-        working_with.insert((1, 0));
-        working_with.insert((0, 1));
-        while !working_with.is_empty() {
-            working_with = self.step(&mut risk_values_in_tiles, working_with);
+            // risks above 9 wrap back around to 1
+            let value = small[(x % small_width, y % small_height)];
+            let value_modified = ((value as usize - 1 + t_x + t_y) % 9 + 1) as u8;
+
+            tiles.set((x, y), value_modified);
         }
 
-        let (m_y, m_x) = self.end;
-        *risk_values_in_tiles.get(m_y, m_x).unwrap()
+        let end = (tiles.width() - 1, tiles.height() - 1);
+        Self { tiles, end }
     }
 
-    fn step(
-        &self,
-        risk_values: &mut Array2D<usize>,
-        working_with: HashSet<Coords>,
-    ) -> HashSet<Coords> {
-        let mut changed = HashSet::new();
-        for (column, row) in working_with {
-            let risk_value = *risk_values.get(row, column).unwrap();
-
-            let neighbours = self.get_neighbours((column, row));
-            for (n_x, n_y) in &neighbours {
-                let neighbour_risk_value = *risk_values.get(*n_y, *n_x).unwrap();
-
-                let potential_risk = risk_value + *self.tiles.get(*n_y, *n_x).unwrap() as usize;
-                if potential_risk < neighbour_risk_value {
-                    risk_values.set(*n_y, *n_x, potential_risk).unwrap();
-                    changed.insert((*n_x, *n_y));
-                }
-            }
-        }
+    pub fn calculate_lowest_risk_value(&self) -> usize {
+        let (end_x, end_y) = self.end;
+        // every tile has a risk of at least 1, so the distance to the end is never an overestimate
+        let distance_to_end = |&(x, y): &Coords| (end_x - x) + (end_y - y);
 
-        changed
+        astar(self, (0, 0), |&tile| tile == self.end, distance_to_end)
+            .unwrap()
+            .cost
     }
+}
 
-    fn get_neighbours(&self, tile: Coords) -> Vec<Coords> {
-        let (x, y) = tile;
-        let (max_x, max_y) = self.end;
-        let mut neighbours = Vec::with_capacity(4);
-        if x != 0 {
-            neighbours.push((x - 1, y));
-        }
-        if y != 0 {
-            neighbours.push((x, y - 1));
-        }
-        if x != max_x {
-            neighbours.push((x + 1, y));
-        }
-        if y != max_y {
-            neighbours.push((x, y + 1));
-        }
+impl Graph for Map {
+    type State = Coords;
 
-        neighbours
+    fn neighbours(&self, &tile: &Coords) -> Vec<(Coords, usize)> {
+        self.tiles
+            .neighbours4(tile)
+            .map(|neighbour| (neighbour, self.tiles[neighbour] as usize))
+            .collect()
     }
 }
 
-fn calculate_original_risk_value(s: &str) {
-    let map = Map::parse(&s);
+fn calculate_risk_value(map: &Map) -> usize {
     let start = Instant::now();
-    let minimum_risk_value_original = map.calculate_lowest_risk_value();
+    let minimum_risk_value = map.calculate_lowest_risk_value();
     let elapsed = start.elapsed();
-    println!("Elapsed time: {:?}", elapsed);
-    println!(
-        "The minimum risk value of the original map is {}",
-        minimum_risk_value_original
-    );
+    debug!("Finding the lowest risk path took {:?}", elapsed);
+    minimum_risk_value
 }
 
-fn calculate_whole_risk_value(s: &str) {
-    let map = Map::parse_whole(&s);
-    let start = Instant::now();
-    let minimum_risk_value_original = map.calculate_lowest_risk_value();
-    let elapsed = start.elapsed();
-    println!("Elapsed time: {:?}", elapsed);
-    println!(
-        "The minimum risk value of the whole map is {}",
-        minimum_risk_value_original
-    );
+/// Generates a `size`x`size` map of risk levels.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    generate_digit_grid(rng, size, 1..=9)
 }
 
-fn main() {
-    let input = read_file_to_string("input/day15.txt");
+/// The full map of the real input takes hundreds of megabytes to search at twice this factor.
+const MAX_TILE_FACTOR: usize = 10;
+
+pub const TILE_FACTOR: Param = Param {
+    name: "tile_factor",
+    default: 5,
+    description: "how many times the full map repeats the scanned one in both directions",
+    check: |factor| Param::in_range(factor, 1..=MAX_TILE_FACTOR),
+};
+
+pub struct Day15;
 
-    calculate_original_risk_value(&input);
-    calculate_whole_risk_value(&input);
+impl Solution for Day15 {
+    type Input = Map;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    const PARAMS: &'static [Param] = &[TILE_FACTOR];
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Map::parse(input)
+    }
+
+    fn part1(&self, map: &Self::Input) -> Self::Answer1 {
+        calculate_risk_value(map)
+    }
+
+    fn part2(&self, map: &Self::Input) -> Self::Answer2 {
+        self.part2_with(map, &Params::default())
+    }
+
+    fn part2_with(&self, map: &Self::Input, params: &Params) -> Self::Answer2 {
+        calculate_risk_value(&map.expand(params.get(&TILE_FACTOR)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+1163751742
+1381373672
+2136511328
+3694931569
+7463417111
+1319128137
+1359912421
+3125421639
+1293138521
+2311944581
+";
+
+    #[test]
+    fn example_part1() {
+        let input = Day15.parse(EXAMPLE).unwrap();
+        assert_eq!(Day15.part1(&input), 40);
+    }
+
+    #[test]
+    fn example_part2() {
+        let input = Day15.parse(EXAMPLE).unwrap();
+        assert_eq!(Day15.part2(&input), 315);
+    }
+
+    #[test]
+    fn expand_wraps_risk_levels_around_to_1() {
+        let map = Map::parse("8\n").unwrap().expand(5);
+        let first_row = map
+            .tiles
+            .rows()
+            .next()
+            .unwrap()
+            .copied()
+            .collect::<Vec<_>>();
+        assert_eq!(first_row, vec![8, 9, 1, 2, 3]);
+    }
+
+    #[test]
+    fn tile_factor_has_to_be_positive_and_small() {
+        assert!(Params::parse(Day15::PARAMS, &["tile_factor=0"]).is_err());
+        assert!(Params::parse(Day15::PARAMS, &["tile_factor=1"]).is_ok());
+        assert!(Params::parse(Day15::PARAMS, &["tile_factor=11"]).is_err());
+    }
 }
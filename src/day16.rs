@@ -1,62 +1,522 @@
-use advent_of_code_2021::read_file_to_string;
-use phf::phf_map;
-use std::convert::identity;
-
-const HEX_TO_BIN: phf::Map<char, &'static str> = phf_map! {
-    '0' => "0000",
-    '1' => "0001",
-    '2' => "0010",
-    '3' => "0011",
-    '4' => "0100",
-    '5' => "0101",
-    '6' => "0110",
-    '7' => "0111",
-    '8' => "1000",
-    '9' => "1001",
-    'A' => "1010",
-    'B' => "1011",
-    'C' => "1100",
-    'D' => "1101",
-    'E' => "1110",
-    'F' => "1111",
-};
+use crate::generate::InputRng;
+use crate::{debug, trace, Error, LineError, Param, Params, Solution};
+use num_bigint::BigUint;
+use rand::{seq::SliceRandom, Rng};
+use std::str::FromStr;
+
+/// Why a transmission could not be decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeErrorReason {
+    InvalidHexDigit(char),
+    /// The transmission ends in the middle of the named field.
+    Truncated(&'static str),
+    NoSubPackets(Operation),
+    NotTwoSubPackets(Operation, usize),
+    /// The sub-packets of an operator take up another number of bits than its total length.
+    LengthMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    /// The packet is nested deeper than [`MAX_DEPTH`].
+    TooDeep,
+}
+
+impl std::fmt::Display for DecodeErrorReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidHexDigit(c) => write!(f, "'{}' is not a hexadecimal digit", c),
+            Self::Truncated(field) => {
+                write!(f, "the transmission ends in the middle of the {}", field)
+            }
+            Self::NoSubPackets(operation) => {
+                write!(f, "'{}' has no sub-packets", operation.name())
+            }
+            Self::NotTwoSubPackets(operation, n) => write!(
+                f,
+                "'{}' compares two sub-packets, not {}",
+                operation.name(),
+                n
+            ),
+            Self::LengthMismatch { expected, actual } => write!(
+                f,
+                "the sub-packets take up {} bits instead of {}",
+                actual, expected
+            ),
+            Self::TooDeep => write!(f, "packets are nested more than {} deep", MAX_DEPTH),
+        }
+    }
+}
+
+/// A transmission that could not be decoded, with the offset in bits of where it goes wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    pub offset: usize,
+    pub reason: DecodeErrorReason,
+}
+
+impl DecodeError {
+    pub fn at(offset: usize, reason: DecodeErrorReason) -> Self {
+        Self { offset, reason }
+    }
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "bit {}: {}", self.offset, self.reason)
+    }
+}
 
-fn parse_input_as_binary_str(path: &str) -> String {
-    let input = read_file_to_string(path);
+/// Points at the hexadecimal digit the offending bit is in.
+impl From<DecodeError> for LineError {
+    fn from(e: DecodeError) -> Self {
+        LineError::at(e.offset / 4 + 1, e.to_string())
+    }
+}
 
-    input
+/// Packs the hexadecimal digits of a transmission two to a byte, padding an odd last one with
+/// zeros.
+pub fn parse_hex(input: &str) -> Result<Vec<u8>, DecodeError> {
+    let digits = input
         .chars()
-        .map(|c| HEX_TO_BIN.get(&c).unwrap().to_string())
-        .collect()
+        .enumerate()
+        .map(|(i, c)| {
+            c.to_digit(16)
+                .map(|digit| digit as u8)
+                .ok_or_else(|| DecodeError::at(4 * i, DecodeErrorReason::InvalidHexDigit(c)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(digits
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair.get(1).copied().unwrap_or(0))
+        .collect())
+}
+
+/// A cursor reading the bits of a transmission, most significant first, a field of a few bits at
+/// a time.
+pub struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    /// The number of bits read so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn remaining(&self) -> usize {
+        8 * self.bytes.len() - self.position
+    }
+
+    /// Reads the next `n` bits as a number, or nothing if fewer than `n` are left.
+    pub fn read(&mut self, n: usize) -> Option<usize> {
+        assert!(n <= usize::BITS as usize, "Cannot read {} bits at once", n);
+        if n > self.remaining() {
+            return None;
+        }
+
+        let mut value = 0;
+        let mut left = n;
+        while left > 0 {
+            let byte = self.bytes[self.position / 8];
+            let offset = self.position % 8;
+            let taken = left.min(8 - offset);
+            let bits = (byte >> (8 - offset - taken)) as usize & ((1 << taken) - 1);
+            value = value << taken | bits;
+            self.position += taken;
+            left -= taken;
+        }
+        Some(value)
+    }
+
+    pub fn read_flag(&mut self) -> Option<bool> {
+        self.read(1).map(|bit| bit == 1)
+    }
+}
+
+/// Appends the bits of a transmission, most significant first, to bytes padded with zeros.
+#[derive(Default)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    position: usize,
+}
+
+impl BitWriter {
+    /// The number of bits written so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Writes the lowest `n` bits of `value`.
+    pub fn write(&mut self, value: usize, n: usize) {
+        for i in (0..n).rev() {
+            if self.position == 8 * self.bytes.len() {
+                self.bytes.push(0);
+            }
+            if value >> i & 1 == 1 {
+                *self.bytes.last_mut().unwrap() |= 0x80 >> (self.position % 8);
+            }
+            self.position += 1;
+        }
+    }
+
+    pub fn write_flag(&mut self, flag: bool) {
+        self.write(flag as usize, 1);
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// What an operator packet computes from the values of its sub-packets, given by its type id.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Operation {
+    Sum,
+    Product,
+    Minimum,
+    Maximum,
+    GreaterThan,
+    LessThan,
+    EqualTo,
+}
+
+impl Operation {
+    pub fn from_type_id(type_id: usize) -> Option<Self> {
+        match type_id {
+            0 => Some(Self::Sum),
+            1 => Some(Self::Product),
+            2 => Some(Self::Minimum),
+            3 => Some(Self::Maximum),
+            5 => Some(Self::GreaterThan),
+            6 => Some(Self::LessThan),
+            7 => Some(Self::EqualTo),
+            _ => None,
+        }
+    }
+
+    pub fn type_id(self) -> usize {
+        match self {
+            Self::Sum => 0,
+            Self::Product => 1,
+            Self::Minimum => 2,
+            Self::Maximum => 3,
+            Self::GreaterThan => 5,
+            Self::LessThan => 6,
+            Self::EqualTo => 7,
+        }
+    }
+
+    const ALL: [Operation; 7] = [
+        Self::Sum,
+        Self::Product,
+        Self::Minimum,
+        Self::Maximum,
+        Self::GreaterThan,
+        Self::LessThan,
+        Self::EqualTo,
+    ];
+
+    /// The operation written as `name` in an expression, see [`Packet::from_str`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|operation| operation.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Sum => "sum",
+            Self::Product => "product",
+            Self::Minimum => "min",
+            Self::Maximum => "max",
+            Self::GreaterThan => "gt",
+            Self::LessThan => "lt",
+            Self::EqualTo => "eq",
+        }
+    }
 }
 
-fn binary_str_to_usize(s: impl AsRef<str>) -> usize {
-    usize::from_str_radix(s.as_ref(), 2).unwrap()
+/// How an operator packet tells where its sub-packets end.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LengthType {
+    /// The sub-packets take up this many bits.
+    TotalLength(usize),
+    /// There are this many sub-packets.
+    NumOfSubPackets(usize),
 }
 
-fn binary_str_to_bool(s: impl AsRef<str>) -> bool {
-    match s.as_ref() {
-        "1" => true,
-        "0" => false,
-        _ => panic!("Cannot convert '{}' to bool", s.as_ref()),
+impl LengthType {
+    /// The total length type of an operator with `sub_packets`.
+    pub fn total_length_of(sub_packets: &[Packet]) -> Self {
+        Self::TotalLength(sub_packets.iter().map(Packet::bit_length).sum())
+    }
+
+    /// The number of sub-packets length type of an operator with `sub_packets`.
+    pub fn num_of(sub_packets: &[Packet]) -> Self {
+        Self::NumOfSubPackets(sub_packets.len())
+    }
+
+    /// The length type of the same kind that matches `sub_packets`.
+    fn recount(self, sub_packets: &[Packet]) -> Self {
+        match self {
+            Self::TotalLength(_) => Self::total_length_of(sub_packets),
+            Self::NumOfSubPackets(_) => Self::num_of(sub_packets),
+        }
     }
 }
 
-fn split_and_map_head<'a, T, F: Fn(&'a str) -> T>(str: &'a str, n: usize, f: F) -> (T, &'a str) {
-    let (head, tail) = str.split_at(n);
-    (f(head), tail)
+/// How deep packets can be nested. Decoding, evaluating and showing a packet recurse once per
+/// level, so deeper transmissions and expressions are rejected rather than overflow the stack.
+pub const MAX_DEPTH: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Packet {
+    Literal {
+        version: usize,
+        value: BigUint,
+        /// The number of 4-bit groups the value is sent in, which may start with groups of zeros.
+        groups: usize,
+    },
+    Operator {
+        version: usize,
+        operation: Operation,
+        length_type: LengthType,
+        sub_packets: Vec<Packet>,
+    },
 }
 
-fn split_off_n_digits(str: &str, n: usize) -> (&str, &str) {
-    split_and_map_head(str, n, identity)
+impl Packet {
+    /// A literal sent in as few groups as its value takes.
+    pub fn literal(version: usize, value: BigUint) -> Self {
+        let groups = num_of_literal_groups(&value);
+        Packet::Literal {
+            version,
+            value,
+            groups,
+        }
+    }
+
+    pub fn version(&self) -> usize {
+        match self {
+            Packet::Literal { version, .. } | Packet::Operator { version, .. } => *version,
+        }
+    }
+
+    pub fn sub_packets(&self) -> &[Packet] {
+        match self {
+            Packet::Literal { .. } => &[],
+            Packet::Operator { sub_packets, .. } => sub_packets,
+        }
+    }
+
+    /// Walks the packet and all packets nested in it, each before its sub-packets.
+    pub fn packets(&self) -> Packets<'_> {
+        Packets { stack: vec![self] }
+    }
+
+    pub fn sum_of_versions(&self) -> usize {
+        self.packets().map(Packet::version).sum()
+    }
+
+    /// The number of packets in the tree, this one included.
+    pub fn num_of_packets(&self) -> usize {
+        self.packets().count()
+    }
+
+    /// The number of packets on the longest path down from this one, which is 1 for a literal.
+    pub fn depth(&self) -> usize {
+        1 + self
+            .sub_packets()
+            .iter()
+            .map(Packet::depth)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The number of bits the packet is encoded in, without any padding.
+    pub fn bit_length(&self) -> usize {
+        match self {
+            Packet::Literal { groups, .. } => 6 + 5 * groups,
+            Packet::Operator {
+                length_type,
+                sub_packets,
+                ..
+            } => {
+                let length_field = match length_type {
+                    LengthType::TotalLength(_) => 15,
+                    LengthType::NumOfSubPackets(_) => 11,
+                };
+                7 + length_field + sub_packets.iter().map(Packet::bit_length).sum::<usize>()
+            }
+        }
+    }
+
+    /// The value of the packet, or `None` if it or any value along the way does not fit in a
+    /// `usize`.
+    pub fn value(&self) -> Option<usize> {
+        let (operation, sub_packets) = match self {
+            Packet::Literal { value, .. } => return usize::try_from(value).ok(),
+            Packet::Operator {
+                operation,
+                sub_packets,
+                ..
+            } => (operation, sub_packets),
+        };
+
+        let values = sub_packets
+            .iter()
+            .map(Packet::value)
+            .collect::<Option<Vec<_>>>()?;
+        let value = match operation {
+            Operation::Sum => values
+                .iter()
+                .try_fold(0usize, |sum, v| sum.checked_add(*v))?,
+            Operation::Product => values
+                .iter()
+                .try_fold(1usize, |product, v| product.checked_mul(*v))?,
+            Operation::Minimum => *values.iter().min().unwrap(),
+            Operation::Maximum => *values.iter().max().unwrap(),
+            Operation::GreaterThan => compare_two_element_slice(&values, PartialOrd::gt),
+            Operation::LessThan => compare_two_element_slice(&values, PartialOrd::lt),
+            Operation::EqualTo => compare_two_element_slice(&values, PartialEq::eq),
+        };
+        Some(value)
+    }
+
+    /// The exact value of the packet, however large it gets.
+    pub fn big_value(&self) -> BigUint {
+        let (operation, sub_packets) = match self {
+            Packet::Literal { value, .. } => return value.clone(),
+            Packet::Operator {
+                operation,
+                sub_packets,
+                ..
+            } => (operation, sub_packets),
+        };
+
+        let values = sub_packets
+            .iter()
+            .map(Packet::big_value)
+            .collect::<Vec<_>>();
+        match operation {
+            Operation::Sum => values.into_iter().sum(),
+            Operation::Product => values.into_iter().product(),
+            Operation::Minimum => values.into_iter().min().unwrap(),
+            Operation::Maximum => values.into_iter().max().unwrap(),
+            Operation::GreaterThan => compare_two_element_slice(&values, PartialOrd::gt).into(),
+            Operation::LessThan => compare_two_element_slice(&values, PartialOrd::lt).into(),
+            Operation::EqualTo => compare_two_element_slice(&values, PartialEq::eq).into(),
+        }
+    }
 }
 
-fn parse_and_remove_n_digits(str: &str, n: usize) -> (usize, &str) {
-    split_and_map_head(str, n, binary_str_to_usize)
+/// Shows the expression the packet evaluates, like `sum(1, max(2, 3))`.
+impl std::fmt::Display for Packet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Packet::Literal { value, .. } => write!(f, "{}", value),
+            Packet::Operator {
+                operation,
+                sub_packets,
+                ..
+            } => {
+                write!(f, "{}(", operation.name())?;
+                for (i, sub_packet) in sub_packets.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", sub_packet)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+/// Parses an expression of the form `Display` writes, like `sum(1, max(2, 3))`, into a packet of
+/// version 0. Operators take the total length type, unless their sub-packets are too long for it.
+impl FromStr for Packet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (packet, rest) = parse_expression(s, 1)?;
+        match rest.trim() {
+            "" => Ok(packet),
+            rest => Err(format!("unexpected '{}' after the expression", rest)),
+        }
+    }
 }
 
-fn parse_and_remove_flag(str: &str) -> (bool, &str) {
-    split_and_map_head(str, 1, binary_str_to_bool)
+/// Parses the expression at the start of `s`, nested `depth` deep, returning what comes after it.
+fn parse_expression(s: &str, depth: usize) -> Result<(Packet, &str), String> {
+    if depth > MAX_DEPTH {
+        return Err(DecodeErrorReason::TooDeep.to_string());
+    }
+    let s = s.trim_start();
+    let (token, rest) = s.split_at(
+        s.find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(s.len()),
+    );
+    if let Ok(value) = token.parse() {
+        return Ok((Packet::literal(0, value), rest));
+    }
+
+    let operation = Operation::from_name(token)
+        .ok_or_else(|| format!("'{}' is neither a number nor an operation", token))?;
+    let mut rest = rest
+        .trim_start()
+        .strip_prefix('(')
+        .ok_or_else(|| format!("expected '(' after '{}'", token))?;
+    let mut sub_packets = Vec::new();
+    loop {
+        let (sub_packet, after) = parse_expression(rest, depth + 1)?;
+        sub_packets.push(sub_packet);
+        let after = after.trim_start();
+        if let Some(after) = after.strip_prefix(',') {
+            rest = after;
+        } else if let Some(after) = after.strip_prefix(')') {
+            rest = after;
+            break;
+        } else {
+            return Err(format!("expected ',' or ')' before '{}'", after));
+        }
+    }
+
+    check_num_of_sub_packets(operation, sub_packets.len()).map_err(|e| e.to_string())?;
+
+    let length_type = match LengthType::total_length_of(&sub_packets) {
+        LengthType::TotalLength(length) if length >= 1 << 15 => LengthType::num_of(&sub_packets),
+        total_length => total_length,
+    };
+    let operator = Packet::Operator {
+        version: 0,
+        operation,
+        length_type,
+        sub_packets,
+    };
+    Ok((operator, rest))
+}
+
+/// Iterator over a packet tree, see [`Packet::packets`].
+pub struct Packets<'a> {
+    stack: Vec<&'a Packet>,
+}
+
+impl<'a> Iterator for Packets<'a> {
+    type Item = &'a Packet;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let packet = self.stack.pop()?;
+        self.stack.extend(packet.sub_packets().iter().rev());
+        Some(packet)
+    }
 }
 
 fn compare_two_element_slice<T: PartialOrd, F: Fn(&T, &T) -> bool>(values: &[T], cmp: F) -> usize {
@@ -70,103 +530,694 @@ fn compare_two_element_slice<T: PartialOrd, F: Fn(&T, &T) -> bool>(values: &[T],
     }
 }
 
-fn process_packet(packet: &str) -> (usize, usize, &str) {
-    let (version, packet) = parse_and_remove_n_digits(packet, 3);
-    let (type_id, packet) = parse_and_remove_n_digits(packet, 3);
+/// Decodes the packet at the reader, leaving it right after the packet.
+pub fn decode_packet(reader: &mut BitReader) -> Result<Packet, DecodeError> {
+    decode_nested_packet(reader, 1)
+}
+
+/// Decodes a packet nested `depth` deep in the transmission.
+fn decode_nested_packet(reader: &mut BitReader, depth: usize) -> Result<Packet, DecodeError> {
+    if depth > MAX_DEPTH {
+        return Err(DecodeError::at(
+            reader.position(),
+            DecodeErrorReason::TooDeep,
+        ));
+    }
+    let version = read_field(reader, 3, "version")?;
+    let type_id = read_field(reader, 3, "type id")?;
 
     if type_id == 4 {
-        let (value, packet) = process_literal(packet);
-        (version, value, packet)
+        let (value, groups) = decode_literal(reader)?;
+        Ok(Packet::Literal {
+            version,
+            value,
+            groups,
+        })
     } else {
-        let (sum_of_versions, values, packet) = process_operator(packet);
-        let value = match type_id {
-            0 => values.iter().sum(),
-            1 => values.iter().product(),
-            2 => *values.iter().min().unwrap(),
-            3 => *values.iter().max().unwrap(),
-            5 => compare_two_element_slice(&values, PartialOrd::gt),
-            6 => compare_two_element_slice(&values, PartialOrd::lt),
-            7 => compare_two_element_slice(&values, PartialEq::eq),
-            _ => panic!("Type id of {} is invalid", type_id),
-        };
-        (sum_of_versions + version, value, packet)
+        // every other 3-bit type id is an operation
+        let operation = Operation::from_type_id(type_id).unwrap();
+        let start = reader.position();
+        let (length_type, sub_packets) = decode_sub_packets(reader, depth + 1)?;
+        check_num_of_sub_packets(operation, sub_packets.len())
+            .map_err(|reason| DecodeError::at(start, reason))?;
+        Ok(Packet::Operator {
+            version,
+            operation,
+            length_type,
+            sub_packets,
+        })
     }
 }
 
-fn process_literal(packet: &str) -> (usize, &str) {
-    let mut value_str = String::new();
-    let (mut group, mut packet) = split_off_n_digits(packet, 5);
+fn read_field(reader: &mut BitReader, n: usize, field: &'static str) -> Result<usize, DecodeError> {
+    let offset = reader.position();
+    reader
+        .read(n)
+        .ok_or_else(|| DecodeError::at(offset, DecodeErrorReason::Truncated(field)))
+}
 
+/// Decodes the value of a literal along with the number of groups it is sent in.
+fn decode_literal(reader: &mut BitReader) -> Result<(BigUint, usize), DecodeError> {
+    let mut digits = Vec::new();
     loop {
-        let (has_more, literal_part) = parse_and_remove_flag(group);
-        value_str += literal_part;
+        let has_more = read_field(reader, 1, "literal group")? == 1;
+        digits.push(read_field(reader, 4, "literal group")? as u8);
         if !has_more {
             break;
         }
-        let (g, p) = split_off_n_digits(packet, 5);
-        group = g;
-        packet = p;
     }
 
-    let numeric_value = binary_str_to_usize(&value_str);
-
-    (numeric_value, packet)
+    let value = BigUint::from_radix_be(&digits, 16).unwrap();
+    Ok((value, digits.len()))
 }
 
-fn process_operator(packet: &str) -> (usize, Vec<usize>, &str) {
-    let (is_length_type_num_of_sub_packets, packet) = parse_and_remove_flag(packet);
+fn decode_sub_packets(
+    reader: &mut BitReader,
+    depth: usize,
+) -> Result<(LengthType, Vec<Packet>), DecodeError> {
+    let is_length_type_num_of_sub_packets = read_field(reader, 1, "length type")? == 1;
 
     if is_length_type_num_of_sub_packets {
-        let (num_of_sub_packets, packet) = parse_and_remove_n_digits(packet, 11);
-        process_sub_packets_num(packet, num_of_sub_packets)
+        let num_of_sub_packets = read_field(reader, 11, "number of sub-packets")?;
+        let sub_packets = decode_sub_packets_num(reader, num_of_sub_packets, depth)?;
+        Ok((LengthType::NumOfSubPackets(num_of_sub_packets), sub_packets))
     } else {
-        let (length, packet) = parse_and_remove_n_digits(packet, 15);
-        process_sub_packets_with_total_length(packet, length)
+        let length = read_field(reader, 15, "total length")?;
+        let sub_packets = decode_sub_packets_with_total_length(reader, length, depth)?;
+        Ok((LengthType::TotalLength(length), sub_packets))
     }
 }
 
-fn process_sub_packets_with_total_length(packet: &str, length: usize) -> (usize, Vec<usize>, &str) {
-    let original_length = packet.len();
-
-    let mut new_length = original_length;
-    let mut sum_of_versions = 0;
-    let mut values = Vec::new();
-    let mut packet = packet;
+fn decode_sub_packets_with_total_length(
+    reader: &mut BitReader,
+    length: usize,
+    depth: usize,
+) -> Result<Vec<Packet>, DecodeError> {
+    let start = reader.position();
+    let end = start + length;
+    let mut sub_packets = Vec::new();
 
-    println!("Processing sub packets of size {}", length);
-    while original_length - length != new_length {
-        println!("Processing sub packet");
-        let (sum, value, p) = process_packet(packet);
-        packet = p;
-        sum_of_versions += sum;
-        values.push(value);
-        new_length = packet.len();
+    debug!("Decoding sub packets of size {}", length);
+    while reader.position() < end {
+        trace!("Decoding sub packet");
+        sub_packets.push(decode_nested_packet(reader, depth)?);
     }
 
-    (sum_of_versions, values, packet)
+    if reader.position() != end {
+        let reason = DecodeErrorReason::LengthMismatch {
+            expected: length,
+            actual: reader.position() - start,
+        };
+        return Err(DecodeError::at(start, reason));
+    }
+    Ok(sub_packets)
 }
 
-fn process_sub_packets_num(packet: &str, n: usize) -> (usize, Vec<usize>, &str) {
-    let mut sum_of_versions = 0;
-    let mut values = Vec::new();
-    let mut packet = packet;
+fn decode_sub_packets_num(
+    reader: &mut BitReader,
+    n: usize,
+    depth: usize,
+) -> Result<Vec<Packet>, DecodeError> {
+    let mut sub_packets = Vec::new();
 
-    println!("Processing {} sub packets", n);
+    debug!("Decoding {} sub packets", n);
     for i in 0..n {
-        println!("Processing sub packet {}", i);
-        let (sum, value, p) = process_packet(packet);
-        packet = p;
-        sum_of_versions += sum;
-        values.push(value);
-        println!("Processed sub packet {}", i);
+        trace!("Decoding sub packet {}", i);
+        sub_packets.push(decode_nested_packet(reader, depth)?);
+        trace!("Decoded sub packet {}", i);
+    }
+
+    Ok(sub_packets)
+}
+
+/// Checks that an operator with `n` sub-packets can be evaluated: every operation needs one at
+/// least, and comparisons exactly two.
+fn check_num_of_sub_packets(operation: Operation, n: usize) -> Result<(), DecodeErrorReason> {
+    let is_comparison = matches!(
+        operation,
+        Operation::GreaterThan | Operation::LessThan | Operation::EqualTo
+    );
+    if n == 0 {
+        Err(DecodeErrorReason::NoSubPackets(operation))
+    } else if is_comparison && n != 2 {
+        Err(DecodeErrorReason::NotTwoSubPackets(operation, n))
+    } else {
+        Ok(())
     }
+}
+
+/// Encodes the packet as a transmission, writing the lengths of operators with the length type
+/// they have.
+///
+/// Panics if a version does not fit in 3 bits, a value does not fit in the groups of its literal,
+/// or a length type does not match the sub-packets or does not fit in its field.
+pub fn encode_packet(packet: &Packet) -> Vec<u8> {
+    let mut writer = BitWriter::default();
+    encode_packet_into(packet, &mut writer);
+    writer.into_bytes()
+}
+
+fn encode_packet_into(packet: &Packet, writer: &mut BitWriter) {
+    assert!(
+        packet.version() < 8,
+        "Version {} does not fit in 3 bits",
+        packet.version()
+    );
+    writer.write(packet.version(), 3);
+    match packet {
+        Packet::Literal { value, groups, .. } => {
+            writer.write(4, 3);
+            encode_literal_into(value, *groups, writer);
+        }
+        Packet::Operator {
+            operation,
+            length_type,
+            sub_packets,
+            ..
+        } => {
+            assert_eq!(
+                *length_type,
+                length_type.recount(sub_packets),
+                "The length type does not match the sub-packets"
+            );
+            writer.write(operation.type_id(), 3);
+            match *length_type {
+                LengthType::TotalLength(length) => {
+                    assert!(length < 1 << 15, "{} bits do not fit in 15 bits", length);
+                    writer.write_flag(false);
+                    writer.write(length, 15);
+                }
+                LengthType::NumOfSubPackets(n) => {
+                    assert!(n < 1 << 11, "{} sub-packets do not fit in 11 bits", n);
+                    writer.write_flag(true);
+                    writer.write(n, 11);
+                }
+            }
+            for sub_packet in sub_packets {
+                encode_packet_into(sub_packet, writer);
+            }
+        }
+    }
+}
 
-    (sum_of_versions, values, packet)
+fn num_of_literal_groups(value: &BigUint) -> usize {
+    (value.bits().max(1) as usize).div_ceil(4)
 }
 
-fn main() {
-    let packet_str = parse_input_as_binary_str("input/day16.txt");
-    let (sum_of_versions, value, _) = process_packet(&packet_str);
-    println!("The sum of all version numbers is {}", sum_of_versions);
-    println!("The resulting value is {}", value);
+fn encode_literal_into(value: &BigUint, groups: usize, writer: &mut BitWriter) {
+    let digits = value.to_radix_be(16);
+    assert!(
+        digits.len() <= groups,
+        "{} does not fit in {} groups",
+        value,
+        groups
+    );
+    let padding = groups - digits.len();
+    for i in 0..groups {
+        writer.write_flag(i + 1 < groups);
+        let digit = if i < padding { 0 } else { digits[i - padding] };
+        writer.write(digit as usize, 4);
+    }
+}
+
+/// Encodes the packet as a transmission in hexadecimal, padded to whole bytes like the real ones.
+pub fn encode_packet_as_hex(packet: &Packet) -> String {
+    encode_packet(packet)
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect()
+}
+
+/// Generates a transmission of `size` packets. Products only multiply a few small literals, so the
+/// value of the transmission always fits in 64 bits.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    encode_packet_as_hex(&generate_packet(rng, size.max(1))) + "\n"
+}
+
+/// Generates a packet made up of `num_of_packets` packets, itself included.
+fn generate_packet(rng: &mut InputRng, num_of_packets: usize) -> Packet {
+    let version = rng.gen_range(0..8);
+    let remaining = num_of_packets - 1;
+    if remaining == 0 {
+        let value = rng.gen_range(0..1usize << 32).into();
+        return Packet::literal(version, value);
+    }
+
+    let (operation, sub_packets) = if remaining <= 4 && rng.gen_bool(0.3) {
+        let factors = (0..remaining)
+            .map(|_| Packet::literal(rng.gen_range(0..8), rng.gen_range(0..256usize).into()))
+            .collect::<Vec<_>>();
+        (Operation::Product, factors)
+    } else {
+        let (operation, num_of_sub_packets) = if remaining >= 2 && rng.gen_bool(0.2) {
+            let comparisons = [
+                Operation::GreaterThan,
+                Operation::LessThan,
+                Operation::EqualTo,
+            ];
+            (*comparisons.choose(rng).unwrap(), 2)
+        } else {
+            let aggregates = [Operation::Sum, Operation::Minimum, Operation::Maximum];
+            (
+                *aggregates.choose(rng).unwrap(),
+                rng.gen_range(1..=remaining.min(4)),
+            )
+        };
+        let sub_packets = split_randomly(rng, remaining, num_of_sub_packets)
+            .into_iter()
+            .map(|n| generate_packet(rng, n))
+            .collect();
+        (operation, sub_packets)
+    };
+
+    let total_length = LengthType::total_length_of(&sub_packets);
+    let length_type = match total_length {
+        LengthType::TotalLength(length) if rng.gen_bool(0.5) && length < 1 << 15 => total_length,
+        _ => LengthType::num_of(&sub_packets),
+    };
+    Packet::Operator {
+        version,
+        operation,
+        length_type,
+        sub_packets,
+    }
+}
+
+/// Splits `total` into `parts` random sizes of at least one.
+fn split_randomly(rng: &mut InputRng, total: usize, parts: usize) -> Vec<usize> {
+    let mut sizes = vec![1; parts];
+    for _ in parts..total {
+        sizes[rng.gen_range(0..parts)] += 1;
+    }
+    sizes
+}
+
+pub const BIG_INTEGERS: Param = Param {
+    name: "big_integers",
+    default: 0,
+    description: "1 to evaluate with integers of any size right away, not once 64 bits overflow",
+    check: |big_integers| Param::in_range(big_integers, 0..=1),
+};
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    type Input = Packet;
+    type Answer1 = usize;
+    type Answer2 = BigUint;
+
+    const PARAMS: &'static [Param] = &[BIG_INTEGERS];
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        let packet = parse_hex(input.trim_end())
+            .and_then(|bytes| decode_packet(&mut BitReader::new(&bytes)))
+            .map_err(|e| Error::malformed(1, e))?;
+        debug!(
+            "Decoded {} packets, nested {} deep: {}",
+            packet.num_of_packets(),
+            packet.depth(),
+            packet
+        );
+        Ok(packet)
+    }
+
+    fn part1(&self, packet: &Self::Input) -> Self::Answer1 {
+        packet.sum_of_versions()
+    }
+
+    fn part2(&self, packet: &Self::Input) -> Self::Answer2 {
+        self.part2_with(packet, &Params::default())
+    }
+
+    fn part2_with(&self, packet: &Self::Input, params: &Params) -> Self::Answer2 {
+        if params.get(&BIG_INTEGERS) == 1 {
+            return packet.big_value();
+        }
+        // 64 bits are much faster and almost always enough, the exact value is only needed when
+        // they overflow
+        match packet.value() {
+            Some(value) => value.into(),
+            None => {
+                debug!("The value overflows {} bits", usize::BITS);
+                packet.big_value()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::generate_input;
+    use proptest::prelude::*;
+    use rand::SeedableRng;
+
+    fn sum_of_versions(hex: &str) -> usize {
+        Day16.part1(&Day16.parse(hex).unwrap())
+    }
+
+    fn value(hex: &str) -> usize {
+        usize::try_from(&Day16.part2(&Day16.parse(hex).unwrap())).unwrap()
+    }
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(sum_of_versions("8A004A801A8002F478"), 16);
+        assert_eq!(sum_of_versions("620080001611562C8802118E34"), 12);
+        assert_eq!(sum_of_versions("C0015000016115A2E0802F182340"), 23);
+        assert_eq!(sum_of_versions("A0016C880162017C3686B18A3D4780"), 31);
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(value("C200B40A82"), 3);
+        assert_eq!(value("04005AC33890"), 54);
+        assert_eq!(value("880086C3E88112"), 7);
+        assert_eq!(value("CE00C43D881120"), 9);
+        assert_eq!(value("D8005AC2A8F0"), 1);
+        assert_eq!(value("F600BC2D8F"), 0);
+        assert_eq!(value("9C005AC2F8F0"), 0);
+        assert_eq!(value("9C0141080250320F1802104A08"), 1);
+    }
+
+    #[test]
+    fn literal_packet() {
+        let bytes = parse_hex("D2FE28").unwrap();
+        assert_eq!(bytes, [0b1101_0010, 0b1111_1110, 0b0010_1000]);
+        let mut reader = BitReader::new(&bytes);
+        let packet = decode_packet(&mut reader).unwrap();
+        assert_eq!(packet, Packet::literal(6, 2021u32.into()));
+        assert_eq!(reader.remaining(), 3);
+    }
+
+    #[test]
+    fn reader_reads_fields_across_bytes() {
+        let bytes = parse_hex("D2FE28F").unwrap();
+        assert_eq!(bytes, [0xD2, 0xFE, 0x28, 0xF0]);
+        let mut reader = BitReader::new(&bytes);
+        assert_eq!(reader.read(3), Some(0b110));
+        assert_eq!(reader.read_flag(), Some(true));
+        assert_eq!(reader.read(9), Some(0b0_0101_1111));
+        assert_eq!(reader.position(), 13);
+        assert_eq!(reader.read(0), Some(0));
+        assert_eq!(reader.read(20), None);
+        assert_eq!(reader.read(15), Some(0b110_0010_1000_1111));
+        assert_eq!(reader.remaining(), 4);
+    }
+
+    #[test]
+    fn writer_writes_what_the_reader_reads() {
+        let mut writer = BitWriter::default();
+        writer.write(0b101, 3);
+        writer.write(usize::MAX, usize::BITS as usize);
+        writer.write_flag(false);
+        assert_eq!(writer.position(), 68);
+
+        let bytes = writer.into_bytes();
+        assert_eq!(bytes.len(), 9);
+        let mut reader = BitReader::new(&bytes);
+        assert_eq!(reader.read(3), Some(0b101));
+        assert_eq!(reader.read(usize::BITS as usize), Some(usize::MAX));
+        assert_eq!(reader.read_flag(), Some(false));
+        assert_eq!(reader.remaining(), 4);
+    }
+
+    #[test]
+    fn operator_packets() {
+        let packet = Day16.parse("38006F45291200").unwrap();
+        assert_eq!(
+            packet,
+            Packet::Operator {
+                version: 1,
+                operation: Operation::LessThan,
+                length_type: LengthType::TotalLength(27),
+                sub_packets: vec![
+                    Packet::literal(6, 10u32.into()),
+                    Packet::literal(2, 20u32.into()),
+                ],
+            }
+        );
+
+        let packet = Day16.parse("EE00D40C823060").unwrap();
+        assert_eq!(
+            packet
+                .sub_packets()
+                .iter()
+                .map(Packet::value)
+                .collect::<Vec<_>>(),
+            [Some(1), Some(2), Some(3)]
+        );
+        assert!(matches!(
+            packet,
+            Packet::Operator {
+                operation: Operation::Maximum,
+                length_type: LengthType::NumOfSubPackets(3),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn packet_tree_walks() {
+        let packet = Day16.parse("A0016C880162017C3686B18A3D4780").unwrap();
+        assert_eq!(packet.num_of_packets(), 8);
+        assert_eq!(packet.depth(), 4);
+        assert_eq!(packet.to_string(), "sum(sum(sum(6, 6, 12, 15, 15)))");
+    }
+
+    #[test]
+    fn generated_packets_have_the_requested_size() {
+        let hex = generate_input(16, Some(40), 7);
+        assert_eq!(Day16.parse(&hex).unwrap().num_of_packets(), 40);
+    }
+
+    #[test]
+    fn parse_rejects_non_hex_digits() {
+        let e = Day16.parse("D2FG28").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 1, column 4: bit 12: 'G' is not a hexadecimal digit"
+        );
+        assert_eq!(value("d2fe28"), 2021);
+    }
+
+    /// Decodes a transmission made up of the given fields, as pairs of a value and its bits.
+    fn decode_fields(fields: &[(usize, usize)]) -> Result<Packet, DecodeError> {
+        let mut writer = BitWriter::default();
+        for &(value, n) in fields {
+            writer.write(value, n);
+        }
+        decode_packet(&mut BitReader::new(&writer.into_bytes()))
+    }
+
+    #[test]
+    fn malformed_transmissions_are_reported_where_they_go_wrong() {
+        let e = Day16.parse("D2FE").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 1, column 5: bit 16: the transmission ends in the middle of the literal group"
+        );
+
+        // gt(7), with a single sub-packet
+        let e = decode_fields(&[(5, 6), (1, 1), (1, 11), (4, 6), (7, 5)]).unwrap_err();
+        assert_eq!(
+            e,
+            DecodeError::at(
+                6,
+                DecodeErrorReason::NotTwoSubPackets(Operation::GreaterThan, 1)
+            )
+        );
+
+        // max(), with a total length of 0
+        let e = decode_fields(&[(3, 6), (0, 1), (0, 15)]).unwrap_err();
+        assert_eq!(
+            e,
+            DecodeError::at(6, DecodeErrorReason::NoSubPackets(Operation::Maximum))
+        );
+
+        // sum(7), claiming 10 bits for a literal of 11
+        let e = decode_fields(&[(0, 6), (0, 1), (10, 15), (4, 6), (7, 5)]).unwrap_err();
+        assert_eq!(
+            e,
+            DecodeError::at(
+                22,
+                DecodeErrorReason::LengthMismatch {
+                    expected: 10,
+                    actual: 11
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn values_beyond_64_bits_are_exact_with_big_integers() {
+        let big_integers = Params::parse(&[BIG_INTEGERS], &["big_integers=1"]).unwrap();
+        assert!(Params::parse(&[BIG_INTEGERS], &["big_integers=7"]).is_err());
+
+        // a literal of 17 hexadecimal digits
+        let mut fields = vec![(4, 6)];
+        fields.extend([(0b11111, 5); 16]);
+        fields.push((0b01111, 5));
+        let literal = decode_fields(&fields).unwrap();
+        assert_eq!(literal.value(), None);
+        assert_eq!(
+            Day16.part2_with(&literal, &big_integers),
+            (BigUint::from(1u32) << 68u32) - 1u32
+        );
+
+        let product = Packet::from_str("product(4294967296, 4294967296, 3)").unwrap();
+        let packet = decode_packet(&mut BitReader::new(&encode_packet(&product))).unwrap();
+        assert_eq!(packet.value(), None);
+        assert_eq!(
+            Day16.part2_with(&packet, &big_integers).to_string(),
+            "55340232221128654848"
+        );
+        let sum = Packet::from_str(&format!("sum({}, 1)", usize::MAX)).unwrap();
+        assert_eq!(sum.value(), None);
+        assert_eq!(sum.big_value(), BigUint::from(usize::MAX) + 1u32);
+        let comparison = Packet::from_str(&format!("gt({}0, {})", usize::MAX, usize::MAX)).unwrap();
+        assert_eq!(comparison.value(), None);
+        assert_eq!(comparison.big_value(), BigUint::from(1u32));
+    }
+
+    #[test]
+    fn values_beyond_64_bits_are_exact_by_default() {
+        let packet = Packet::from_str("sum(product(4294967296, 4294967296), 1)").unwrap();
+        let hex = encode_packet_as_hex(&packet);
+        assert_eq!(
+            Day16.part2(&Day16.parse(&hex).unwrap()).to_string(),
+            "18446744073709551617"
+        );
+    }
+
+    #[test]
+    fn literal_is_encoded_like_the_example() {
+        let packet = Packet::literal(6, 2021u32.into());
+        assert_eq!(encode_packet(&packet), [0xD2, 0xFE, 0x28]);
+        assert_eq!(packet.bit_length(), 21);
+    }
+
+    #[test]
+    fn examples_survive_a_round_trip() {
+        let examples = [
+            "D2FE28",
+            "38006F45291200",
+            "EE00D40C823060",
+            "8A004A801A8002F478",
+            "620080001611562C8802118E34",
+            "C0015000016115A2E0802F182340",
+            "A0016C880162017C3686B18A3D4780",
+            "9C0141080250320F1802104A08",
+        ];
+        for hex in examples {
+            let packet = Day16.parse(hex).unwrap();
+            let encoded = encode_packet_as_hex(&packet);
+            assert!(hex.starts_with(&encoded), "{} became {}", hex, encoded);
+            assert_eq!(Day16.parse(&encoded).unwrap(), packet);
+        }
+    }
+
+    #[test]
+    fn literals_padded_with_zero_groups_survive_a_round_trip() {
+        // sum(5, 1), with the 5 sent in three groups and the total length counting all of them
+        let mut writer = BitWriter::default();
+        writer.write(1, 3);
+        writer.write(0, 3);
+        writer.write_flag(false);
+        writer.write(21 + 11, 15);
+        writer.write(2, 3);
+        writer.write(4, 3);
+        for (has_more, digit) in [(true, 0), (true, 0), (false, 5)] {
+            writer.write_flag(has_more);
+            writer.write(digit, 4);
+        }
+        writer.write(3, 3);
+        writer.write(4, 3);
+        writer.write(1, 5);
+        let bytes = writer.into_bytes();
+
+        let packet = decode_packet(&mut BitReader::new(&bytes)).unwrap();
+        assert_eq!(
+            packet.sub_packets()[0],
+            Packet::Literal {
+                version: 2,
+                value: 5u32.into(),
+                groups: 3
+            }
+        );
+        assert_eq!(packet.value(), Some(6));
+        assert_eq!(packet.bit_length(), 22 + 21 + 11);
+        assert_eq!(encode_packet(&packet), bytes);
+    }
+
+    #[test]
+    fn expressions_are_encoded_as_transmissions() {
+        let packet = Packet::from_str("sum(1, max(2, 3), product(4, 5))").unwrap();
+        assert_eq!(packet.to_string(), "sum(1, max(2, 3), product(4, 5))");
+        assert_eq!(value(&encode_packet_as_hex(&packet)), 24);
+        assert_eq!(
+            value(&encode_packet_as_hex(&"eq(7, gt(9, 8))".parse().unwrap())),
+            0
+        );
+
+        assert!(Packet::from_str("sum(1, 2").is_err());
+        assert!(Packet::from_str("lt(1, 2, 3)").is_err());
+        assert!(Packet::from_str("mean(1, 2)").is_err());
+        assert!(Packet::from_str("sum(1) 2").is_err());
+    }
+
+    /// A transmission of sums of one sub-packet each, nested `depth` deep around a literal 7.
+    fn nested_sums(depth: usize) -> Vec<u8> {
+        let mut writer = BitWriter::default();
+        for _ in 1..depth {
+            writer.write(0, 6);
+            writer.write_flag(true);
+            writer.write(1, 11);
+        }
+        writer.write(4, 6);
+        writer.write(7, 5);
+        writer.into_bytes()
+    }
+
+    #[test]
+    fn packets_nested_too_deep_are_rejected() {
+        let bytes = nested_sums(MAX_DEPTH);
+        let packet = decode_packet(&mut BitReader::new(&bytes)).unwrap();
+        assert_eq!(packet.depth(), MAX_DEPTH);
+        assert_eq!(packet.value(), Some(7));
+        assert_eq!(packet.big_value(), 7u32.into());
+        assert_eq!(encode_packet(&packet), bytes);
+        let expression = packet.to_string();
+        assert_eq!(
+            Packet::from_str(&expression).unwrap().to_string(),
+            expression
+        );
+
+        for depth in [MAX_DEPTH + 1, 1_000_000] {
+            let error = decode_packet(&mut BitReader::new(&nested_sums(depth))).unwrap_err();
+            assert_eq!(
+                error,
+                DecodeError::at(18 * MAX_DEPTH, DecodeErrorReason::TooDeep)
+            );
+        }
+        let expression = format!("{}7{}", "sum(".repeat(MAX_DEPTH), ")".repeat(MAX_DEPTH));
+        assert_eq!(
+            Packet::from_str(&expression).unwrap_err(),
+            "packets are nested more than 256 deep"
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn generated_packets_survive_a_round_trip(seed in any::<u64>(), size in 1..300usize) {
+            let packet = generate_packet(&mut InputRng::seed_from_u64(seed), size);
+            let bytes = encode_packet(&packet);
+            let mut reader = BitReader::new(&bytes);
+            let decoded = decode_packet(&mut reader).unwrap();
+            prop_assert_eq!(reader.position(), packet.bit_length());
+            prop_assert_eq!(bytes.len(), packet.bit_length().div_ceil(8));
+            prop_assert_eq!(decoded.value(), packet.value());
+            prop_assert_eq!(decoded, packet);
+        }
+    }
 }
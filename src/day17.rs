@@ -1,4 +1,6 @@
-use advent_of_code_2021::read_file_to_string;
+use crate::generate::InputRng;
+use crate::{trace, Error, Solution};
+use rand::Rng;
 use sscanf::scanf;
 use std::ops::RangeInclusive;
 
@@ -6,20 +8,32 @@ fn sum_of_first_n_integers(n: i32) -> i32 {
     n * (n + 1) / 2
 }
 
-struct TargetArea {
+pub struct TargetArea {
     range_x: RangeInclusive<i32>,
     range_y: RangeInclusive<i32>,
 }
 
 impl TargetArea {
-    pub fn parse(s: &str) -> Self {
+    pub fn parse(s: &str) -> Result<Self, String> {
         let (start_x, end_x, start_y, end_y) =
-            scanf!(s, "target area: x={}..{}, y={}..{}", i32, i32, i32, i32).unwrap();
+            scanf!(s, "target area: x={}..{}, y={}..{}", i32, i32, i32, i32).ok_or_else(|| {
+                format!(
+                    "'{}' is not of the form 'target area: x=x1..x2, y=y1..y2'",
+                    s
+                )
+            })?;
+
+        // the solution assumes the target is in front of and below the launcher
+        if start_x > end_x || start_y > end_y || start_x <= 0 || end_y >= 0 {
+            return Err(
+                "the target area must lie to the right of and below the launcher".to_string(),
+            );
+        }
 
-        Self {
+        Ok(Self {
             range_x: start_x..=end_x,
             range_y: start_y..=end_y,
-        }
+        })
     }
 
     pub fn contains_probe(&self, probe: &Probe) -> bool {
@@ -41,7 +55,7 @@ impl TargetArea {
 }
 
 #[derive(Debug)]
-struct Probe {
+pub struct Probe {
     x: i32,
     y: i32,
     vx: i32,
@@ -75,7 +89,7 @@ impl Probe {
     }
 }
 
-struct Trench {
+pub struct Trench {
     target_area: TargetArea,
 }
 
@@ -96,7 +110,7 @@ impl Trench {
         self.run_simulation()
             .iter()
             .filter_map(|&r| r)
-            .inspect(|(_, vx, vy)| println!("{},{}", vx, vy))
+            .inspect(|(_, vx, vy)| trace!("Valid throw: ({},{})", vx, vy))
             .count()
     }
 
@@ -105,9 +119,9 @@ impl Trench {
         for probe in self.generate_probes_with_plausible_starting_velocities() {
             let vx = probe.vx;
             let vy = probe.vy;
-            println!("Throwing probe: ({},{})", &probe.vx, &probe.vy);
+            trace!("Throwing probe: ({},{})", &probe.vx, &probe.vy);
             let result = self.throw_probe(probe);
-            println!("Result of throw: {:?}", &result);
+            trace!("Result of throw: {:?}", &result);
             results.push(result.map(|r| (r, vx, vy)));
         }
 
@@ -132,7 +146,7 @@ impl Trench {
     fn throw_probe(&self, mut probe: Probe) -> Option<i32> {
         let mut max_y = i32::MIN;
         loop {
-            dbg!(&probe);
+            trace!("{:?}", &probe);
             if self.target_area.contains_probe(&probe) {
                 return Some(max_y);
             }
@@ -145,15 +159,64 @@ impl Trench {
     }
 }
 
-fn main() {
-    let input = read_file_to_string("input/day17.txt");
+/// Generates a target area `size` wide and half as deep, to the right of and below the launcher.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    let size = size.max(1) as i32;
+    let start_x = rng.gen_range(10..200);
+    let end_y = -rng.gen_range(5..200);
+    format!(
+        "target area: x={}..{}, y={}..{}\n",
+        start_x,
+        start_x + size - 1,
+        end_y - size / 2,
+        end_y
+    )
+}
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    type Input = Trench;
+    type Answer1 = i32;
+    type Answer2 = usize;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        let target_area = TargetArea::parse(input.trim()).map_err(|e| Error::malformed(1, e))?;
+        Ok(Trench::new(target_area))
+    }
+
+    fn part1(&self, trench: &Self::Input) -> Self::Answer1 {
+        trench.calculate_maximum_y_for_all_throws()
+    }
+
+    fn part2(&self, trench: &Self::Input) -> Self::Answer2 {
+        trench.count_number_of_valid_throws()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let target_area = TargetArea::parse(&input);
-    let trench = Trench::new(target_area);
+    const EXAMPLE: &str = "target area: x=20..30, y=-10..-5\n";
 
-    let maximum_y = trench.calculate_maximum_y_for_all_throws();
-    println!("The maximum y value of all throws is {}", maximum_y);
+    #[test]
+    fn example_part1() {
+        let input = Day17.parse(EXAMPLE).unwrap();
+        assert_eq!(Day17.part1(&input), 45);
+    }
+
+    #[test]
+    fn example_part2() {
+        let input = Day17.parse(EXAMPLE).unwrap();
+        assert_eq!(Day17.part2(&input), 112);
+    }
 
-    let num_of_valid_throws = trench.count_number_of_valid_throws();
-    println!("The number of valid throws is {}", num_of_valid_throws);
+    #[test]
+    fn probe_reaches_target_area() {
+        let target_area = TargetArea::parse(EXAMPLE.trim()).unwrap();
+        let trench = Trench::new(target_area);
+        assert_eq!(trench.throw_probe(Probe::new(6, 9)), Some(45));
+        assert_eq!(trench.throw_probe(Probe::new(17, -4)), None);
+    }
 }
@@ -1,10 +1,12 @@
-use advent_of_code_2021::read_file_lines_as;
+use crate::generate::InputRng;
+use crate::{debug, trace, try_parse_lines_as, Error, LineError, Solution};
 use itertools::Itertools;
+use rand::Rng;
 
-type Expression = Vec<Token>;
+pub type Expression = Vec<Token>;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
-enum Token {
+pub enum Token {
     Start,
     End,
     Value(u32),
@@ -34,15 +36,20 @@ impl std::fmt::Display for Token {
     }
 }
 
-fn parse_expression(line: &str) -> Expression {
-    line.chars().filter_map(Token::from_char).collect()
+fn parse_expression(line: &str) -> Result<Expression, LineError> {
+    line.chars()
+        .enumerate()
+        // commas only separate the elements of a pair, so they are not kept as tokens
+        .filter(|&(_, c)| c != ',')
+        .map(|(i, c)| {
+            Token::from_char(c)
+                .ok_or_else(|| LineError::at(i + 1, format!("'{}' is not part of a number", c)))
+        })
+        .collect()
 }
 
-fn print_expression(expression: &Expression) {
-    for t in expression {
-        print!("{}", t);
-    }
-    println!();
+fn format_expression(expression: &Expression) -> String {
+    expression.iter().map(Token::to_string).collect()
 }
 
 fn add_expressions(lhs: &Expression, rhs: &Expression) -> Expression {
@@ -83,11 +90,10 @@ fn try_explode_first_eligible_pair(expression: &mut Expression) -> bool {
             *value += y;
         }
 
-        print_expression(&expression);
+        trace!("Exploding:  {}", format_expression(expression));
         *expression.get_mut(position - 1).unwrap() = Token::Value(0);
-        print_expression(&expression);
         expression.drain(position..(position + 3));
-        print_expression(&expression);
+        trace!("Exploded:   {}", format_expression(expression));
 
         true
     } else {
@@ -219,15 +225,14 @@ fn calculate_magnitude_rec(acc: u32, part: &[Token]) -> (u32, &[Token]) {
     }
 }
 
-fn find_homework_magnitude(expressions: Vec<Expression>) {
+fn find_homework_magnitude(expressions: Vec<Expression>) -> u32 {
     let result = calculate_sum_of_expressions(expressions);
-    print_expression(&result);
-    let magnitude = calculate_magnitude(&result);
-    println!("The magnitude is {}", magnitude);
+    debug!("Sum of the homework: {}", format_expression(&result));
+    calculate_magnitude(&result)
 }
 
-fn find_largest_magnitude_from_sum(expressions: &Vec<Expression>) {
-    let max_magnitude = expressions
+fn find_largest_magnitude_from_sum(expressions: &[Expression]) -> u32 {
+    expressions
         .iter()
         .cartesian_product(expressions.iter())
         .filter_map(|(e1, e2)| {
@@ -240,16 +245,106 @@ fn find_largest_magnitude_from_sum(expressions: &Vec<Expression>) {
             }
         })
         .max()
-        .unwrap();
+        .unwrap()
+}
+
+/// Generates `size` snailfish numbers, nesting pairs at most four deep.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    fn element(rng: &mut InputRng, depth: usize) -> String {
+        if depth == 0 || (depth < 4 && rng.gen_bool(0.6)) {
+            format!("[{},{}]", element(rng, depth + 1), element(rng, depth + 1))
+        } else {
+            rng.gen_range(0..10).to_string()
+        }
+    }
+
+    (0..size).map(|_| element(rng, 0) + "\n").collect()
+}
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    type Input = Vec<Expression>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        try_parse_lines_as(input, parse_expression)
+    }
+
+    fn part1(&self, expressions: &Self::Input) -> Self::Answer1 {
+        find_homework_magnitude(expressions.clone())
+    }
 
-    println!(
-        "The largest magnitude value of all possible sums is {}",
-        max_magnitude
-    );
+    fn part2(&self, expressions: &Self::Input) -> Self::Answer2 {
+        find_largest_magnitude_from_sum(expressions)
+    }
 }
 
-fn main() {
-    let expressions = read_file_lines_as("input/day18.txt", parse_expression);
-    find_homework_magnitude(expressions.clone());
-    find_largest_magnitude_from_sum(&expressions);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]
+[[[5,[2,8]],4],[5,[[9,9],0]]]
+[6,[[[6,2],[5,6]],[[7,6],[4,7]]]]
+[[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]
+[[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]
+[[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]
+[[[[5,4],[7,7]],8],[[8,3],8]]
+[[9,3],[[9,9],[6,[4,9]]]]
+[[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]
+[[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]
+";
+
+    fn expression(s: &str) -> Expression {
+        parse_expression(s).unwrap()
+    }
+
+    #[test]
+    fn example_part1() {
+        let input = Day18.parse(EXAMPLE).unwrap();
+        assert_eq!(Day18.part1(&input), 4140);
+    }
+
+    #[test]
+    fn example_part2() {
+        let input = Day18.parse(EXAMPLE).unwrap();
+        assert_eq!(Day18.part2(&input), 3993);
+    }
+
+    #[test]
+    fn addition_explodes_and_splits() {
+        let sum = add_expressions(
+            &expression("[[[[4,3],4],4],[7,[[8,4],9]]]"),
+            &expression("[1,1]"),
+        );
+        assert_eq!(sum, expression("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]"));
+    }
+
+    #[test]
+    fn explode_leftmost_pair() {
+        let mut e = expression("[[[[[9,8],1],2],3],4]");
+        assert!(try_explode_first_eligible_pair(&mut e));
+        assert_eq!(e, expression("[[[[0,9],2],3],4]"));
+    }
+
+    #[test]
+    fn split_values() {
+        assert_eq!(calculate_split_values(10), (5, 5));
+        assert_eq!(calculate_split_values(11), (5, 6));
+        assert_eq!(calculate_split_values(12), (6, 6));
+    }
+
+    #[test]
+    fn magnitude() {
+        assert_eq!(calculate_magnitude(&expression("[[1,2],[[3,4],5]]")), 143);
+        assert_eq!(
+            calculate_magnitude(&expression(
+                "[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]"
+            )),
+            3488
+        );
+    }
 }
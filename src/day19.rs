@@ -1,6 +1,8 @@
-use advent_of_code_2021::read_file_to_string;
+use crate::generate::InputRng;
+use crate::{debug, split_blocks, Error, Param, Params, Solution};
 use itertools::Itertools;
 use phf::phf_map;
+use rand::{seq::SliceRandom, Rng};
 use sscanf::scanf;
 use std::collections::{HashSet, VecDeque};
 use std::str::FromStr;
@@ -33,7 +35,7 @@ const ORIENTATIONS: phf::Map<u8, fn(&Vec3) -> Vec3> = phf_map! {
 };
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
-struct Vec3 {
+pub struct Vec3 {
     data: [i32; 3],
 }
 
@@ -82,7 +84,8 @@ impl Vec3 {
 impl FromStr for Vec3 {
     type Err = String;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (x, y, z) = scanf!(s, "{},{},{}", i32, i32, i32).ok_or("Invalid format".to_string())?;
+        let (x, y, z) = scanf!(s, "{},{},{}", i32, i32, i32)
+            .ok_or_else(|| format!("'{}' is not a position of the form 'x,y,z'", s))?;
 
         Ok(Self { data: [x, y, z] })
     }
@@ -117,23 +120,32 @@ impl std::fmt::Display for Vec3 {
 }
 
 #[derive(Debug, Clone)]
-struct Scanner {
+pub struct Scanner {
     id: usize,
     beacons: HashSet<Vec3>,
 }
 
 impl Scanner {
-    pub fn parse(s: &str) -> Self {
-        let (head, tail) = s.split_once("\r\n").unwrap();
-        let id = scanf!(head, "--- scanner {} ---", usize).unwrap();
-        let beacons = tail.lines().map(|l| Vec3::from_str(l).unwrap()).collect();
-
-        Self { id, beacons }
+    /// Parses a single scanner report, with line numbers in errors relative to its header.
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        let mut lines = s.lines();
+        let head = lines.next().ok_or(Error::EmptyInput)?;
+        let id = scanf!(head, "--- scanner {} ---", usize)
+            .ok_or_else(|| Error::malformed(1, format!("'{}' is not a scanner header", head)))?;
+        let beacons = lines
+            .enumerate()
+            .map(|(i, l)| Vec3::from_str(l).map_err(|e| Error::malformed(i + 2, e)))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { id, beacons })
     }
 
+    /// Finds the orientation and position of the scanner at which at least `overlap` of its
+    /// beacons are known already, returning all of its beacons and its position there.
     pub fn find_overlapping_points_with(
         &self,
         known_beacons: &HashSet<Vec3>,
+        overlap: usize,
     ) -> Option<(HashSet<Vec3>, Vec3)> {
         for known_beacon in known_beacons {
             for orientation_id in 0..24 {
@@ -154,7 +166,7 @@ impl Scanner {
                     let num_of_matching_beacons =
                         known_beacons.intersection(&translated_beacons).count();
 
-                    if num_of_matching_beacons >= 12 {
+                    if num_of_matching_beacons >= overlap {
                         return Some((translated_beacons, possible_translation));
                     }
                 }
@@ -164,39 +176,48 @@ impl Scanner {
     }
 }
 
-fn discover_all_beacons(scanners: Vec<Scanner>) -> (HashSet<Vec3>, HashSet<Vec3>) {
+/// Places every scanner relative to the first one, returning the positions of all beacons and
+/// scanners, or an error if some scanners share fewer than `overlap` beacons with the others.
+fn discover_all_beacons(
+    scanners: Vec<Scanner>,
+    overlap: usize,
+) -> Result<(HashSet<Vec3>, HashSet<Vec3>), Error> {
     let mut remaining = VecDeque::from(scanners);
     let scanner_0 = remaining.pop_front().unwrap();
     let mut found_beacons: HashSet<Vec3> = HashSet::from_iter(scanner_0.beacons.into_iter());
     let mut beacon_positions: HashSet<Vec3> = HashSet::new();
     beacon_positions.insert(Vec3::zero());
 
+    // once every remaining scanner has been tried without finding a new one, none ever will be
+    let mut num_of_failed_candidates = 0;
     while !remaining.is_empty() {
-        println!("{} unhandled scanners remain", remaining.len());
+        if num_of_failed_candidates == remaining.len() {
+            return Err(Error::Invalid(format!(
+                "{} scanners do not overlap with the others in {} beacons",
+                remaining.len(),
+                overlap
+            )));
+        }
+        debug!("{} unhandled scanners remain", remaining.len());
         let candidate = remaining.pop_front().unwrap();
         if let Some((new_beacons, scanner_position)) =
-            candidate.find_overlapping_points_with(&found_beacons)
+            candidate.find_overlapping_points_with(&found_beacons, overlap)
         {
-            println!("Found overlap with scanner {}", &candidate.id);
+            debug!("Found overlap with scanner {}", &candidate.id);
             found_beacons.extend(new_beacons.iter());
             beacon_positions.insert(scanner_position);
+            num_of_failed_candidates = 0;
         } else {
             remaining.push_back(candidate);
+            num_of_failed_candidates += 1;
         }
     }
 
-    (found_beacons, beacon_positions)
+    Ok((found_beacons, beacon_positions))
 }
 
-fn map_ocean_trench(scanners: Vec<Scanner>) {
-    let (all_beacons, scanner_positions) = discover_all_beacons(scanners);
-    let num_of_all_beacons = all_beacons.len();
-    println!(
-        "The number of all beacons in the trench is {}",
-        num_of_all_beacons
-    );
-
-    let max_distance_between_scanners = scanner_positions
+fn find_max_distance_between_scanners(scanner_positions: &HashSet<Vec3>) -> i32 {
+    scanner_positions
         .iter()
         .cartesian_product(scanner_positions.iter())
         .filter_map(|(p1, p2)| {
@@ -207,20 +228,324 @@ fn map_ocean_trench(scanners: Vec<Scanner>) {
             }
         })
         .max()
-        .unwrap();
+        .unwrap()
+}
+
+/// Generates `size` scanners, each placed so it shares at least 12 beacons with an earlier one,
+/// which is what it takes to find the position of every scanner. Every scanner faces a random
+/// direction.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    const RANGE: i32 = 1000;
+
+    fn random_between(rng: &mut InputRng, min: &Vec3, max: &Vec3) -> Vec3 {
+        Vec3::new(
+            rng.gen_range(min.x()..=max.x()),
+            rng.gen_range(min.y()..=max.y()),
+            rng.gen_range(min.z()..=max.z()),
+        )
+    }
 
-    println!(
-        "The maximum Manhattan distance between any 2 scanners is {}",
-        max_distance_between_scanners
-    );
+    let range = Vec3::new(RANGE, RANGE, RANGE);
+    let mut scanners = vec![Vec3::zero()];
+    let mut beacons = Vec::new();
+    while scanners.len() < size {
+        let neighbour = *scanners.choose(rng).unwrap();
+        let offset = random_between(
+            rng,
+            &Vec3::new(-1200, -1200, -1200),
+            &Vec3::new(1200, 1200, 1200),
+        );
+        let scanner = &neighbour + &offset;
+
+        let min = Vec3::new(
+            neighbour.x().max(scanner.x()),
+            neighbour.y().max(scanner.y()),
+            neighbour.z().max(scanner.z()),
+        );
+        let max = Vec3::new(
+            neighbour.x().min(scanner.x()),
+            neighbour.y().min(scanner.y()),
+            neighbour.z().min(scanner.z()),
+        );
+        for _ in 0..12 {
+            beacons.push(random_between(rng, &(&min - &range), &(&max + &range)));
+        }
+        scanners.push(scanner);
+    }
+    for scanner in &scanners {
+        for _ in 0..rng.gen_range(5..15) {
+            beacons.push(random_between(
+                rng,
+                &(scanner - &range),
+                &(scanner + &range),
+            ));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    beacons.retain(|&beacon| seen.insert(beacon));
+
+    let reports = scanners.iter().enumerate().map(|(id, scanner)| {
+        let orientation = rng.gen_range(0..24);
+        let mut report = beacons
+            .iter()
+            .map(|beacon| beacon - scanner)
+            .filter(|b| b.x().abs() <= RANGE && b.y().abs() <= RANGE && b.z().abs() <= RANGE)
+            .map(|b| {
+                let b = b.rotate(orientation);
+                format!("{},{},{}\n", b.x(), b.y(), b.z())
+            })
+            .collect::<Vec<_>>();
+        report.shuffle(rng);
+        format!("--- scanner {} ---\n{}", id, report.concat())
+    });
+
+    reports.collect::<Vec<_>>().join("\n")
 }
 
-fn main() {
-    let input = read_file_to_string("input/day19.txt");
-    let scanners = input
-        .split("\r\n\r\n")
-        .map(Scanner::parse)
-        .collect::<Vec<_>>();
+pub const OVERLAP: Param = Param {
+    name: "overlap",
+    default: 12,
+    description:
+        "the beacons two scanners need to have in common to be placed relative to each other",
+    check: |overlap| Param::in_range(overlap, 1..=usize::MAX),
+};
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    /// The positions of all beacons and of all scanners, found while parsing as placing the
+    /// scanners can fail for some overlaps.
+    type Input = (HashSet<Vec3>, HashSet<Vec3>);
+    type Answer1 = usize;
+    type Answer2 = i32;
+
+    const PARAMS: &'static [Param] = &[OVERLAP];
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        self.parse_with(input, &Params::default())
+    }
+
+    fn parse_with(&self, input: &str, params: &Params) -> Result<Self::Input, Error> {
+        let scanners = split_blocks(input)
+            .iter()
+            .map(|block| block.parse_with(Scanner::parse))
+            .collect::<Result<Vec<_>, _>>()?;
+        discover_all_beacons(scanners, params.get(&OVERLAP))
+    }
 
-    map_ocean_trench(scanners.clone());
+    fn part1(&self, (beacons, _): &Self::Input) -> Self::Answer1 {
+        beacons.len()
+    }
+
+    fn part2(&self, (_, scanner_positions): &Self::Input) -> Self::Answer2 {
+        find_max_distance_between_scanners(scanner_positions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+--- scanner 0 ---
+404,-588,-901
+528,-643,409
+-838,591,734
+390,-675,-793
+-537,-823,-458
+-485,-357,347
+-345,-311,381
+-661,-816,-575
+-876,649,763
+-618,-824,-621
+553,345,-567
+474,580,667
+-447,-329,318
+-584,868,-557
+544,-627,-890
+564,392,-477
+455,729,728
+-892,524,684
+-689,845,-530
+423,-701,434
+7,-33,-71
+630,319,-379
+443,580,662
+-789,900,-551
+459,-707,401
+
+--- scanner 1 ---
+686,422,578
+605,423,415
+515,917,-361
+-336,658,858
+95,138,22
+-476,619,847
+-340,-569,-846
+567,-361,727
+-460,603,-452
+669,-402,600
+729,430,532
+-500,-761,534
+-322,571,750
+-466,-666,-811
+-429,-592,574
+-355,545,-477
+703,-491,-529
+-328,-685,520
+413,935,-424
+-391,539,-444
+586,-435,557
+-364,-763,-893
+807,-499,-711
+755,-354,-619
+553,889,-390
+
+--- scanner 2 ---
+649,640,665
+682,-795,504
+-784,533,-524
+-644,584,-595
+-588,-843,648
+-30,6,44
+-674,560,763
+500,723,-460
+609,671,-379
+-555,-800,653
+-675,-892,-343
+697,-426,-610
+578,704,681
+493,664,-388
+-671,-858,530
+-667,343,800
+571,-461,-707
+-138,-166,112
+-889,563,-600
+646,-828,498
+640,759,510
+-630,509,768
+-681,-892,-333
+673,-379,-804
+-742,-814,-386
+577,-820,562
+
+--- scanner 3 ---
+-589,542,597
+605,-692,669
+-500,565,-823
+-660,373,557
+-458,-679,-417
+-488,449,543
+-626,468,-788
+338,-750,-386
+528,-832,-391
+562,-778,733
+-938,-730,414
+543,643,-506
+-524,371,-870
+407,773,750
+-104,29,83
+378,-903,-323
+-778,-728,485
+426,699,580
+-438,-605,-362
+-469,-447,-387
+509,732,623
+647,635,-688
+-868,-804,481
+614,-800,639
+595,780,-596
+
+--- scanner 4 ---
+727,592,562
+-293,-554,779
+441,611,-461
+-714,465,-776
+-743,427,-804
+-660,-479,-426
+832,-632,460
+927,-485,-438
+408,393,-506
+466,436,-512
+110,16,151
+-258,-428,682
+-393,719,612
+-211,-452,876
+808,-476,-593
+-575,615,604
+-485,667,467
+-680,325,-822
+-627,-443,-432
+872,-547,-609
+833,512,582
+807,604,487
+839,-516,451
+891,-625,532
+-652,-548,-490
+30,-46,-14
+";
+
+    #[test]
+    fn example_part1() {
+        let input = Day19.parse(EXAMPLE).unwrap();
+        assert_eq!(Day19.part1(&input), 79);
+    }
+
+    #[test]
+    fn example_part2() {
+        let input = Day19.parse(EXAMPLE).unwrap();
+        assert_eq!(Day19.part2(&input), 3621);
+    }
+
+    #[test]
+    fn rotate_yields_24_distinct_orientations() {
+        let v = Vec3::new(1, 2, 3);
+        let rotated = (0..24).map(|id| v.rotate(id)).collect::<HashSet<_>>();
+        assert_eq!(rotated.len(), 24);
+        for r in &rotated {
+            let mut lengths = [r.x().abs(), r.y().abs(), r.z().abs()];
+            lengths.sort_unstable();
+            assert_eq!(lengths, [1, 2, 3]);
+        }
+    }
+
+    #[test]
+    fn rotate_keeps_handedness() {
+        // the cross product of the rotated x and y axes has to stay the rotated z axis
+        for id in 0..24 {
+            let x = Vec3::new(1, 0, 0).rotate(id);
+            let y = Vec3::new(0, 1, 0).rotate(id);
+            let z = Vec3::new(0, 0, 1).rotate(id);
+            let cross = Vec3::new(
+                x.y() * y.z() - x.z() * y.y(),
+                x.z() * y.x() - x.x() * y.z(),
+                x.x() * y.y() - x.y() * y.x(),
+            );
+            assert_eq!(cross, z, "orientation {}", id);
+        }
+    }
+
+    #[test]
+    fn scanners_sharing_too_few_beacons_are_invalid() {
+        let thirteen = Params::parse(&[OVERLAP], &["overlap=13"]).unwrap();
+        assert_eq!(
+            Day19
+                .parse_with(EXAMPLE, &thirteen)
+                .unwrap_err()
+                .to_string(),
+            "invalid input: 4 scanners do not overlap with the others in 13 beacons"
+        );
+    }
+
+    #[test]
+    fn parse_reports_line_in_whole_input() {
+        let e = Day19
+            .parse("--- scanner 0 ---\n1,2,3\n\n--- scanner 1 ---\n1,2\n")
+            .unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 5: '1,2' is not a position of the form 'x,y,z'"
+        );
+    }
 }
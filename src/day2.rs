@@ -1,8 +1,10 @@
-use advent_of_code_2021::read_file_lines_as;
+use crate::generate::InputRng;
+use crate::{info, try_parse_lines_as, Error, Solution};
+use rand::Rng;
 use std::str::FromStr;
 
 #[derive(Debug)]
-enum Direction {
+pub enum Direction {
     Forward,
     Down,
     Up,
@@ -21,7 +23,7 @@ impl FromStr for Direction {
 }
 
 #[derive(Debug)]
-struct Command {
+pub struct Command {
     dir: Direction,
     amount: u32,
 }
@@ -40,14 +42,8 @@ impl FromStr for Command {
     }
 }
 
-impl Command {
-    fn from_str_unwrapped(s: &str) -> Self {
-        Self::from_str(s).unwrap()
-    }
-}
-
 #[derive(Debug)]
-struct Submarine {
+pub struct Submarine {
     position: u32,
     depth: u32,
     aim: u32,
@@ -72,18 +68,23 @@ impl Submarine {
         self.position * self.depth
     }
 
-    fn execute(&mut self, command: &Command) {
+    // first assignment
+    fn execute_without_aim(&mut self, command: &Command) {
         use Direction::*;
         let Command { dir, amount } = command;
 
-        // first assignment
-        /*match dir {
+        match dir {
             Forward => self.position += amount,
             Down => self.depth += amount,
             Up => self.depth -= amount,
-        }*/
+        }
+    }
+
+    // second assignment
+    fn execute(&mut self, command: &Command) {
+        use Direction::*;
+        let Command { dir, amount } = command;
 
-        // second assignment
         match dir {
             Forward => {
                 self.position += amount;
@@ -94,6 +95,12 @@ impl Submarine {
         }
     }
 
+    fn execute_commands_without_aim(&mut self, commands: &Vec<Command>) {
+        for command in commands {
+            self.execute_without_aim(command);
+        }
+    }
+
     fn execute_commands(&mut self, commands: &Vec<Command>) {
         for command in commands {
             self.execute(command);
@@ -101,12 +108,78 @@ impl Submarine {
     }
 }
 
-fn main() {
-    let commands = read_file_lines_as("input/day2.txt", Command::from_str_unwrapped);
+/// Generates `size` commands that never take the submarine above the surface.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    let mut depth = 0;
+    (0..size)
+        .map(|_| {
+            let units = rng.gen_range(1..=9);
+            let direction = match rng.gen_range(0..3) {
+                0 if depth >= units => {
+                    depth -= units;
+                    "up"
+                }
+                1 => {
+                    depth += units;
+                    "down"
+                }
+                _ => "forward",
+            };
+            format!("{} {}\n", direction, units)
+        })
+        .collect()
+}
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    type Input = Vec<Command>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        try_parse_lines_as(input, Command::from_str)
+    }
+
+    fn part1(&self, commands: &Self::Input) -> Self::Answer1 {
+        let mut submarine = Submarine::new();
+        submarine.execute_commands_without_aim(commands);
+
+        info!("Final coordinates are: {}", submarine);
+        submarine.solution()
+    }
+
+    fn part2(&self, commands: &Self::Input) -> Self::Answer2 {
+        let mut submarine = Submarine::new();
+        submarine.execute_commands(commands);
 
-    let mut submarine = Submarine::new();
-    submarine.execute_commands(&commands);
+        info!("Final coordinates are: {}", submarine);
+        submarine.solution()
+    }
+}
 
-    println!("Final coordinates are: {}", submarine);
-    println!("Solution is: {}", submarine.solution());
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2
+";
+
+    #[test]
+    fn example_part1() {
+        let input = Day2.parse(EXAMPLE).unwrap();
+        assert_eq!(Day2.part1(&input), 150);
+    }
+
+    #[test]
+    fn example_part2() {
+        let input = Day2.parse(EXAMPLE).unwrap();
+        assert_eq!(Day2.part2(&input), 900);
+    }
 }
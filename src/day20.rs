@@ -1,18 +1,42 @@
-use advent_of_code_2021::read_file_lines_extract_first;
-use array2d::Array2D;
+use crate::generate::InputRng;
+use crate::{
+    debug,
+    render::{Animation, Frame, Palette},
+    split_blocks, Error, Grid, LineError, Param, Params, Solution,
+};
+use rand::Rng;
 
-struct EnhancementAlgorithm {
+const ALGORITHM_LENGTH: usize = 512;
+
+fn parse_pixel(c: char) -> Result<bool, String> {
+    match c {
+        '#' => Ok(true),
+        '.' => Ok(false),
+        _ => Err(format!("'{}' is not a pixel", c)),
+    }
+}
+
+pub struct EnhancementAlgorithm {
     enhancements: Vec<bool>,
 }
 
 impl EnhancementAlgorithm {
-    pub fn parse(s: &str) -> Self {
+    pub fn parse(s: &str) -> Result<Self, LineError> {
         let enhancements = s
             .chars()
-            .map(|c| if c == '#' { true } else { false })
-            .collect();
+            .enumerate()
+            .map(|(i, c)| parse_pixel(c).map_err(|e| LineError::at(i + 1, e)))
+            .collect::<Result<Vec<_>, _>>()?;
 
-        Self { enhancements }
+        if enhancements.len() != ALGORITHM_LENGTH {
+            return Err(LineError::from(format!(
+                "expected {} enhancements, found {}",
+                ALGORITHM_LENGTH,
+                enhancements.len()
+            )));
+        }
+
+        Ok(Self { enhancements })
     }
 
     pub fn get(&self, index: usize) -> bool {
@@ -20,35 +44,25 @@ impl EnhancementAlgorithm {
     }
 }
 
-struct Image {
-    pixels: Array2D<bool>,
+pub struct Image {
+    pixels: Grid<bool>,
 }
 
 impl Image {
-    pub fn parse(lines: &[String]) -> Self {
-        let height = lines.len() + 2;
-        let width = lines.first().unwrap().len() + 2;
-
-        let mut pixels = Array2D::filled_with(false, height, width);
-
-        for (y, line) in lines.iter().enumerate() {
-            for (x, c) in line.chars().enumerate() {
-                let x = x + 1;
-                let y = y + 1;
-                if c == '#' {
-                    pixels.set(y, x, true).unwrap();
-                }
-            }
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        let image = Grid::parse(s, parse_pixel)?;
+
+        let mut pixels = Grid::filled_with(false, image.width() + 2, image.height() + 2);
+
+        for ((x, y), &pixel) in image.iter() {
+            pixels.set((x + 1, y + 1), pixel);
         }
 
-        Self { pixels }
+        Ok(Self { pixels })
     }
 
     pub fn count_lit_pixels(&self) -> usize {
-        self.pixels
-            .elements_row_major_iter()
-            .filter(|&p| *p)
-            .count()
+        self.pixels.values().filter(|&p| *p).count()
     }
 
     pub fn enhance_twice(&self, algorithm: &EnhancementAlgorithm) -> Self {
@@ -59,36 +73,33 @@ impl Image {
     }
 
     fn crop(&self, n: usize) -> Self {
-        let mut pixels = Array2D::filled_with(
+        let mut pixels = Grid::filled_with(
             false,
-            self.pixels.num_rows() - 2 * n,
-            self.pixels.num_columns() - 2 * n,
+            self.pixels.width() - 2 * n,
+            self.pixels.height() - 2 * n,
         );
 
-        for (y, x) in pixels.indices_row_major() {
-            let cropped_x = x + n;
-            let cropped_y = y + n;
-
-            let value = *self.pixels.get(cropped_y, cropped_x).unwrap();
-            pixels.set(y, x, value).unwrap();
+        for (x, y) in pixels.coords() {
+            let value = self.pixels[(x + n, y + n)];
+            pixels.set((x, y), value);
         }
 
         Self { pixels }
     }
 
     pub fn enhance(&self, widen: usize, heighten: usize, algorithm: &EnhancementAlgorithm) -> Self {
-        let mut pixels = Array2D::filled_with(
+        let mut pixels = Grid::filled_with(
             false,
-            self.pixels.num_rows() + heighten,
-            self.pixels.num_columns() + widen,
+            self.pixels.width() + widen,
+            self.pixels.height() + heighten,
         );
 
-        for (y, x) in pixels.indices_row_major() {
+        for (x, y) in pixels.coords() {
             let input_x = x as isize - (heighten / 2) as isize;
             let input_y = y as isize - (heighten / 2) as isize;
             let index = self.calculate_enhancement_index(input_x, input_y);
             let pixel = algorithm.get(index);
-            pixels.set(y, x, pixel).unwrap();
+            pixels.set((x, y), pixel);
         }
 
         Self { pixels }
@@ -114,73 +125,207 @@ impl Image {
         ];
 
         area.iter()
-            .map(|(x, y)| {
-                if *x >= 0 && *y >= 0 {
-                    if *self.pixels.get(*y as usize, *x as usize).unwrap_or(&false) {
-                        1
-                    } else {
-                        0
-                    }
-                } else {
-                    0
-                }
-            })
+            .map(|&(x, y)| *self.pixels.get_or(x, y, &false) as u8)
             .collect()
     }
+}
 
-    pub fn print(&self) {
-        println!(
+impl std::fmt::Display for Image {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
             "Image is {}x{}",
-            self.pixels.num_rows(),
-            self.pixels.num_columns()
-        );
-        for row in self.pixels.rows_iter() {
-            for column in row.into_iter() {
-                let c = if *column { '#' } else { '.' };
-                print!("{}", c);
+            self.pixels.height(),
+            self.pixels.width()
+        )?;
+        for row in self.pixels.rows() {
+            for column in row {
+                write!(f, "{}", if *column { '#' } else { '.' })?;
             }
-            println!();
+            writeln!(f)?;
         }
-        println!();
+        Ok(())
     }
 }
 
-fn count_lit_pixels_after_2_enhancements(algorithm: &EnhancementAlgorithm, starting_image: &Image) {
-    let result = starting_image.enhance_twice(&algorithm);
-    result.print();
-
-    let num_of_lit_pixels = result.count_lit_pixels();
-    println!(
-        "The number of lit pixels after 2 enhancements is {}",
-        num_of_lit_pixels
-    );
-}
-
-fn count_lit_pixels_after_50_enhancements(
+fn count_lit_pixels_after_enhancements(
     algorithm: &EnhancementAlgorithm,
     starting_image: &Image,
-) {
-    let result = (0..24)
-        .into_iter()
-        .fold(starting_image.enhance_twice(&algorithm), |acc, _| {
-            acc.enhance_twice(&algorithm)
+    num_of_enhancements: usize,
+) -> usize {
+    let result = (1..num_of_enhancements / 2)
+        .fold(starting_image.enhance_twice(algorithm), |acc, _| {
+            acc.enhance_twice(algorithm)
         });
 
-    result.print();
+    debug!("{}", result);
+
+    result.count_lit_pixels()
+}
+
+/// Renders the image before and after each pair of the 50 enhancements; the image grows with every
+/// pair, so the later frames are larger.
+pub fn animate(algorithm: &EnhancementAlgorithm, image: &Image) -> Animation {
+    let render = |image: &Image| Frame::from_grid(&image.pixels, |&lit| lit as u8);
 
-    let num_of_lit_pixels = result.count_lit_pixels();
-    println!(
-        "The number of lit pixels after 50 enhancements is {}",
-        num_of_lit_pixels
-    );
+    let mut frames = vec![render(image)];
+    let mut image = image.enhance_twice(algorithm);
+    frames.push(render(&image));
+    for _ in 0..24 {
+        image = image.enhance_twice(algorithm);
+        frames.push(render(&image));
+    }
+
+    Animation {
+        frames,
+        palette: Palette::new(vec![[0, 0, 0], [255, 255, 255]]),
+        scale: 3,
+    }
 }
 
-fn main() {
-    let (enhancement_rules, image) = read_file_lines_extract_first("input/day20.txt");
+/// Generates an enhancement algorithm and a `size`x`size` image. When the algorithm lights up dark
+/// surroundings it also darkens lit ones, otherwise the infinite image would be lit forever.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    let pixel = |lit: bool| if lit { '#' } else { '.' };
+
+    let mut algorithm = (0..ALGORITHM_LENGTH)
+        .map(|_| rng.gen_bool(0.5))
+        .collect::<Vec<_>>();
+    if algorithm[0] {
+        algorithm[ALGORITHM_LENGTH - 1] = false;
+    }
+    let image = (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| pixel(rng.gen_bool(0.5)))
+                .collect::<String>()
+                + "\n"
+        })
+        .collect::<String>();
+
+    format!(
+        "{}\n\n{}",
+        algorithm.into_iter().map(pixel).collect::<String>(),
+        image
+    )
+}
+
+/// The image grows by a pixel on every side with each enhancement, so each one takes longer than
+/// the one before.
+const MAX_ENHANCEMENTS: usize = 200;
+
+/// An algorithm may light up the whole infinite background and turn it back off the next time, so
+/// only an image enhanced an even number of times has a finite number of lit pixels.
+fn check_enhancements(num_of_enhancements: usize) -> Result<(), String> {
+    if (2..=MAX_ENHANCEMENTS).contains(&num_of_enhancements) && num_of_enhancements % 2 != 1 {
+        Ok(())
+    } else {
+        Err(format!(
+            "expected an even number from 2 to {}",
+            MAX_ENHANCEMENTS
+        ))
+    }
+}
 
-    let algorithm = EnhancementAlgorithm::parse(&enhancement_rules);
-    let image = Image::parse(&image);
+pub const PART1_ENHANCEMENTS: Param = Param {
+    name: "part1_enhancements",
+    default: 2,
+    description: "the enhancements of the first part, an even number of them",
+    check: check_enhancements,
+};
 
-    count_lit_pixels_after_2_enhancements(&algorithm, &image);
-    count_lit_pixels_after_50_enhancements(&algorithm, &image);
+pub const PART2_ENHANCEMENTS: Param = Param {
+    name: "part2_enhancements",
+    default: 50,
+    description: "the enhancements of the second part, an even number of them",
+    check: check_enhancements,
+};
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    type Input = (EnhancementAlgorithm, Image);
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    const PARAMS: &'static [Param] = &[PART1_ENHANCEMENTS, PART2_ENHANCEMENTS];
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        let blocks = split_blocks(input);
+        let (algorithm_block, image_block) = match &blocks[..] {
+            [algorithm, image] => (algorithm, image),
+            [] => return Err(Error::EmptyInput),
+            [_] => return Err(Error::Invalid("there is no image to enhance".to_string())),
+            [_, _, extra, ..] => {
+                return Err(Error::malformed(
+                    extra.first_line,
+                    "expected nothing after the image",
+                ))
+            }
+        };
+
+        let algorithm = EnhancementAlgorithm::parse(algorithm_block.single_line()?)
+            .map_err(|e| Error::malformed(algorithm_block.first_line, e))?;
+        let image = image_block.parse_with(Image::parse)?;
+
+        Ok((algorithm, image))
+    }
+
+    fn part1(&self, input: &Self::Input) -> Self::Answer1 {
+        self.part1_with(input, &Params::default())
+    }
+
+    fn part2(&self, input: &Self::Input) -> Self::Answer2 {
+        self.part2_with(input, &Params::default())
+    }
+
+    fn part1_with(&self, (algorithm, image): &Self::Input, params: &Params) -> Self::Answer1 {
+        count_lit_pixels_after_enhancements(algorithm, image, params.get(&PART1_ENHANCEMENTS))
+    }
+
+    fn part2_with(&self, (algorithm, image): &Self::Input, params: &Params) -> Self::Answer2 {
+        count_lit_pixels_after_enhancements(algorithm, image, params.get(&PART2_ENHANCEMENTS))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+..#.#..#####.#.#.#.###.##.....###.##.#..###.####..#####..#....#..#..##..###..######.###...####..#..#####..##..#.#####...##.#.#..#.##..#.#......#.###.######.###.####...#.##.##..#..#..#####.....#.#....###..#.##......#.....#..#..#..##..#...##.######.####.####.#.#...#.......#..#.#.#...####.##.#......#..#...##.#.##..#...##.#.##..###.#......#.#.......#.#.#.####.###.##...#.....####.#..#..#.##.#....##..#.####....##...##..#...#......#.#.......#.......##..####..#...#.#.#...##..#.#..###..#####........#..####......#..#
+
+#..#.
+#....
+##..#
+..#..
+..###
+";
+
+    #[test]
+    fn example_part1() {
+        let input = Day20.parse(EXAMPLE).unwrap();
+        assert_eq!(Day20.part1(&input), 35);
+    }
+
+    #[test]
+    fn example_part2() {
+        let input = Day20.parse(EXAMPLE).unwrap();
+        assert_eq!(Day20.part2(&input), 3351);
+    }
+
+    #[test]
+    fn parse_rejects_short_algorithm() {
+        let e = Day20.parse("..#\n\n#..#.\n").err().unwrap();
+        assert_eq!(e.to_string(), "line 1: expected 512 enhancements, found 3");
+    }
+
+    #[test]
+    fn enhancements_have_to_come_in_few_pairs() {
+        for enhancements in ["0", "1", "3", "202"] {
+            let assignment = format!("part2_enhancements={}", enhancements);
+            assert!(Params::parse(Day20::PARAMS, &[assignment]).is_err());
+        }
+        assert!(Params::parse(Day20::PARAMS, &["part1_enhancements=4"]).is_ok());
+    }
 }
@@ -1,10 +1,13 @@
-use advent_of_code_2021::read_file_to_string;
+use crate::generate::InputRng;
+use crate::{info, trace, Error, Param, Params, Solution};
 use itertools::Itertools;
+use rand::Rng;
 use sscanf::scanf;
 use std::collections::HashMap;
 use std::str::FromStr;
 
-struct Player {
+#[derive(Clone)]
+pub struct Player {
     id: u8,
     position: u8,
     score: u32,
@@ -13,8 +16,14 @@ struct Player {
 impl FromStr for Player {
     type Err = String;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (id, position) =
-            scanf!(s, "Player {} starting position: {}", u8, u8).ok_or("Invalid format")?;
+        let (id, position) = scanf!(s, "Player {} starting position: {}", u8, u8)
+            .ok_or_else(|| format!("'{}' is not of the form 'Player N starting position: P'", s))?;
+        if !(1..=10).contains(&position) {
+            return Err(format!(
+                "{} is not a space on the board, expected 1-10",
+                position
+            ));
+        }
         Ok(Self {
             id,
             position,
@@ -24,19 +33,17 @@ impl FromStr for Player {
 }
 
 impl Player {
-    pub fn roll_and_step(&mut self, dice: &mut Dice) -> bool {
+    pub fn roll_and_step(&mut self, dice: &mut Dice, winning_score: u32) -> bool {
         let amount = dice.roll_three_times();
-        println!("Player {} rolled {}", self.id, amount);
+        trace!("Player {} rolled {}", self.id, amount);
         self.step(amount);
-        println!(
+        trace!(
             "Player {} moves to space {} for a total score of {}",
-            self.id, self.position, self.score
+            self.id,
+            self.position,
+            self.score
         );
-        self.has_won()
-    }
-
-    fn has_won(&self) -> bool {
-        self.score >= 1000
+        self.score >= winning_score
     }
 
     fn step(&mut self, amount: u32) {
@@ -50,7 +57,7 @@ impl Player {
     }
 }
 
-struct Dice {
+pub struct Dice {
     next: u8,
     counter: u32,
 }
@@ -80,37 +87,39 @@ impl Dice {
         let second = self.roll();
         let third = self.roll();
         let sum = first + second + third;
-        println!("Rolled {}+{}+{}={}", first, second, third, sum);
+        trace!("Rolled {}+{}+{}={}", first, second, third, sum);
         sum
     }
 }
 
-struct Game {
+pub struct Game {
     player_1: Player,
     player_2: Player,
     dice: Dice,
+    winning_score: u32,
 }
 
 impl Game {
-    pub fn parse(s: &str) -> Self {
-        let mut lines = s.lines();
-        let player_1 = Player::from_str(lines.next().unwrap()).unwrap();
-        let player_2 = Player::from_str(lines.next().unwrap()).unwrap();
-
+    pub fn new(player_1: Player, player_2: Player, winning_score: u32) -> Self {
         Self {
             player_1,
             player_2,
             dice: Dice::new(),
+            winning_score,
         }
     }
 
     pub fn play(&mut self) -> u32 {
         loop {
-            let has_player_1_won = self.player_1.roll_and_step(&mut self.dice);
+            let has_player_1_won = self
+                .player_1
+                .roll_and_step(&mut self.dice, self.winning_score);
             if has_player_1_won {
                 return self.calculate_required_value(&self.player_2);
             }
-            let has_player_2_won = self.player_2.roll_and_step(&mut self.dice);
+            let has_player_2_won = self
+                .player_2
+                .roll_and_step(&mut self.dice, self.winning_score);
             if has_player_2_won {
                 return self.calculate_required_value(&self.player_1);
             }
@@ -118,7 +127,7 @@ impl Game {
     }
 
     fn calculate_required_value(&self, losing_player: &Player) -> u32 {
-        println!(
+        info!(
             "Score of losing player: {}, dice counter: {}",
             losing_player.score, self.dice.counter
         );
@@ -126,28 +135,29 @@ impl Game {
     }
 }
 
-struct QuantumGame {
+pub struct QuantumGame {
     // p1: player 1, p2: player 2
     // s: score, p: position
     //               p1s p1p p2s p2p
     states: HashMap<(u8, u8, u8, u8), u64>,
     player_1_won: u64,
     player_2_won: u64,
+    winning_score: u8,
 }
 
 impl QuantumGame {
-    pub fn parse(s: &str) -> Self {
-        let mut lines = s.lines();
-        let player_1_pos = Player::from_str(lines.next().unwrap()).unwrap().position;
-        let player_2_pos = Player::from_str(lines.next().unwrap()).unwrap().position;
+    pub fn new(player_1: &Player, player_2: &Player, winning_score: u8) -> Self {
+        let player_1_pos = player_1.position;
+        let player_2_pos = player_2.position;
 
-        let mut states = Self::generate_empty_state_map();
+        let mut states = Self::generate_empty_state_map(winning_score);
         states.insert((0, player_1_pos, 0, player_2_pos), 1);
 
         QuantumGame {
             states,
             player_1_won: 0,
             player_2_won: 0,
+            winning_score,
         }
     }
 
@@ -166,7 +176,7 @@ impl QuantumGame {
     }
 
     fn step(&mut self, is_player_1_turn: bool) {
-        let mut new_states = Self::generate_empty_state_map();
+        let mut new_states = Self::generate_empty_state_map(self.winning_score);
 
         let rolls = Self::generate_dice_rolls();
 
@@ -177,14 +187,14 @@ impl QuantumGame {
             for roll in &rolls {
                 if is_player_1_turn {
                     let (new_pos, new_score) =
-                        Self::calculate_new_position_and_score(p1p, p1s, roll);
+                        self.calculate_new_position_and_score(p1p, p1s, roll);
 
                     *new_states
                         .get_mut(&(new_score, new_pos, *p2s, *p2p))
                         .unwrap() += n;
                 } else {
                     let (new_pos, new_score) =
-                        Self::calculate_new_position_and_score(p2p, p2s, roll);
+                        self.calculate_new_position_and_score(p2p, p2s, roll);
 
                     *new_states
                         .get_mut(&(*p1s, *p1p, new_score, new_pos))
@@ -196,12 +206,12 @@ impl QuantumGame {
         let mut num_of_wins = 0;
         for ((p1s, _p1p, p2s, _p2p), n) in &mut new_states {
             if is_player_1_turn {
-                if *p1s == 21 {
+                if *p1s == self.winning_score {
                     num_of_wins += *n;
                     *n = 0;
                 }
             } else {
-                if *p2s == 21 {
+                if *p2s == self.winning_score {
                     num_of_wins += *n;
                     *n = 0;
                 }
@@ -217,9 +227,10 @@ impl QuantumGame {
         self.states = new_states;
     }
 
-    fn calculate_new_position_and_score(position: &u8, score: &u8, roll: &u8) -> (u8, u8) {
+    fn calculate_new_position_and_score(&self, position: &u8, score: &u8, roll: &u8) -> (u8, u8) {
         let new_position = (position + roll - 1) % 10 + 1;
-        let new_score = (score + new_position).min(21);
+        // scores stop at the winning one, which a move could otherwise carry past `u8::MAX`
+        let new_score = score.saturating_add(new_position).min(self.winning_score);
 
         (new_position, new_score)
     }
@@ -235,8 +246,8 @@ impl QuantumGame {
             .collect::<Vec<_>>()
     }
 
-    fn generate_empty_state_map() -> HashMap<(u8, u8, u8, u8), u64> {
-        let player_1_state = (0..=21).into_iter().cartesian_product(1..=10);
+    fn generate_empty_state_map(winning_score: u8) -> HashMap<(u8, u8, u8, u8), u64> {
+        let player_1_state = (0..=winning_score).cartesian_product(1..=10);
         let player_2_state = player_1_state.clone();
         let state_iter = player_1_state
             .cartesian_product(player_2_state)
@@ -246,17 +257,127 @@ impl QuantumGame {
     }
 }
 
-fn main() {
-    let input = read_file_to_string("input/day21.txt");
+fn parse_players(s: &str) -> Result<(Player, Player), Error> {
+    let mut lines = s.lines();
+    let mut parse_player = |line_number: usize| {
+        let line = lines
+            .next()
+            .ok_or_else(|| Error::Invalid(format!("player {} is missing", line_number)))?;
+        Player::from_str(line).map_err(|e| Error::malformed(line_number, e))
+    };
+    let player_1 = parse_player(1)?;
+    let player_2 = parse_player(2)?;
+
+    Ok((player_1, player_2))
+}
+
+/// Generates the starting positions of both players; there is nothing for `size` to change.
+pub fn generate(rng: &mut InputRng, _size: usize) -> String {
+    format!(
+        "Player 1 starting position: {}\nPlayer 2 starting position: {}\n",
+        rng.gen_range(1..=10),
+        rng.gen_range(1..=10)
+    )
+}
+
+pub const WINNING_SCORE: Param = Param {
+    name: "winning_score",
+    default: 1000,
+    description: "the score that wins a game with the deterministic dice",
+    check: |score| Param::in_range(score, 1..=u32::MAX as usize),
+};
+
+/// Above this winning score, the universes a player wins in no longer fit in a `u64` for every pair
+/// of starting positions.
+const MAX_QUANTUM_WINNING_SCORE: usize = 27;
 
-    let mut game = Game::parse(&input);
-    let score_of_losing_player_times_num_of_dice_rolls = game.play();
-    println!("The product of the losing player's score and number of dice rolls in the practice game is {}", score_of_losing_player_times_num_of_dice_rolls);
+pub const QUANTUM_WINNING_SCORE: Param = Param {
+    name: "quantum_winning_score",
+    default: 21,
+    description: "the score that wins a game with the Dirac dice",
+    check: |score| Param::in_range(score, 1..=MAX_QUANTUM_WINNING_SCORE),
+};
+
+pub struct Day21;
+
+impl Solution for Day21 {
+    type Input = (Player, Player);
+    type Answer1 = u32;
+    type Answer2 = u64;
+
+    const PARAMS: &'static [Param] = &[WINNING_SCORE, QUANTUM_WINNING_SCORE];
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        parse_players(input)
+    }
+
+    fn part1(&self, players: &Self::Input) -> Self::Answer1 {
+        self.part1_with(players, &Params::default())
+    }
+
+    fn part2(&self, players: &Self::Input) -> Self::Answer2 {
+        self.part2_with(players, &Params::default())
+    }
 
-    let mut quantum_game = QuantumGame::parse(&input);
-    let num_of_wins_for_player_who_wins_more = quantum_game.play();
-    println!(
-        "The number of wins for the player who wins more in the quantum game is {}",
-        num_of_wins_for_player_who_wins_more
-    );
+    fn part1_with(&self, (player_1, player_2): &Self::Input, params: &Params) -> Self::Answer1 {
+        let winning_score = params.get(&WINNING_SCORE) as u32;
+        let mut game = Game::new(player_1.clone(), player_2.clone(), winning_score);
+        game.play()
+    }
+
+    fn part2_with(&self, (player_1, player_2): &Self::Input, params: &Params) -> Self::Answer2 {
+        let winning_score = params.get(&QUANTUM_WINNING_SCORE) as u8;
+        let mut quantum_game = QuantumGame::new(player_1, player_2, winning_score);
+        quantum_game.play()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Player 1 starting position: 4
+Player 2 starting position: 8
+";
+
+    #[test]
+    fn example_part1() {
+        let input = Day21.parse(EXAMPLE).unwrap();
+        assert_eq!(Day21.part1(&input), 739785);
+    }
+
+    #[test]
+    fn example_part2() {
+        let input = Day21.parse(EXAMPLE).unwrap();
+        assert_eq!(Day21.part2(&input), 444356092776315);
+    }
+
+    #[test]
+    fn deterministic_dice_rolls_wrap_around() {
+        let mut dice = Dice::new();
+        assert_eq!(dice.roll_three_times(), 1 + 2 + 3);
+        for _ in 0..32 {
+            dice.roll_three_times();
+        }
+        assert_eq!(dice.roll_three_times(), 100 + 1 + 2);
+    }
+
+    #[test]
+    fn winning_scores_have_to_fit_the_scores() {
+        assert!(Params::parse(Day21::PARAMS, &["winning_score=0"]).is_err());
+        assert!(Params::parse(Day21::PARAMS, &["winning_score=4294967296"]).is_err());
+        assert!(Params::parse(Day21::PARAMS, &["quantum_winning_score=28"]).is_err());
+        assert!(Params::parse(Day21::PARAMS, &["quantum_winning_score=27"]).is_ok());
+    }
+
+    #[test]
+    fn largest_quantum_winning_score_does_not_overflow() {
+        // the starting positions that win in the most universes
+        let (player_1, player_2) = Day21
+            .parse("Player 1 starting position: 6\nPlayer 2 starting position: 6\n")
+            .unwrap();
+        let wins = QuantumGame::new(&player_1, &player_2, MAX_QUANTUM_WINNING_SCORE as u8).play();
+        assert!(wins > 10u64.pow(18));
+    }
 }
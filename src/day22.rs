@@ -1,6 +1,9 @@
-use advent_of_code_2021::read_file_to_string;
+use crate::generate::InputRng;
+use crate::{try_parse_lines_as, Error, LineError, Solution};
+use rand::Rng;
 use sscanf::scanf;
 use std::collections::HashSet;
+use std::ops::Range;
 use std::ops::RangeInclusive;
 use std::str::FromStr;
 
@@ -13,7 +16,7 @@ fn sort(a: i64, b: i64) -> (i64, i64) {
 }
 
 #[derive(Clone)]
-struct Step {
+pub struct Step {
     from: (i64, i64, i64),
     to: (i64, i64, i64),
     on: bool,
@@ -62,10 +65,9 @@ impl Step {
     }
 
     pub fn calculate_volume(&self) -> i64 {
-        let volume =
-            (self.to.0 - self.from.0 + 1) *
-                (self.to.1 - self.from.1 + 1) *
-                (self.to.2 - self.from.2 + 1);
+        let volume = (self.to.0 - self.from.0 + 1)
+            * (self.to.1 - self.from.1 + 1)
+            * (self.to.2 - self.from.2 + 1);
 
         if self.on {
             volume
@@ -76,7 +78,7 @@ impl Step {
 }
 
 impl FromStr for Step {
-    type Err = String;
+    type Err = LineError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let (state, x1, x2, y1, y2, z1, z2) = scanf!(
             s,
@@ -89,9 +91,23 @@ impl FromStr for Step {
             i64,
             i64
         )
-            .ok_or("Invalid format")?;
+        .ok_or_else(|| {
+            format!(
+                "'{}' is not a step of the form 'on x=x1..x2,y=y1..y2,z=z1..z2'",
+                s
+            )
+        })?;
 
-        let on = if state == "on" { true } else { false };
+        let on = match state.as_str() {
+            "on" => true,
+            "off" => false,
+            _ => {
+                return Err(LineError::at(
+                    1,
+                    format!("'{}' is neither 'on' nor 'off'", state),
+                ))
+            }
+        };
         let (from_x, to_x) = sort(x1, x2);
         let (from_y, to_y) = sort(y1, y2);
         let (from_z, to_z) = sort(z1, z2);
@@ -104,18 +120,18 @@ impl FromStr for Step {
     }
 }
 
-struct RebootSequence {
+pub struct RebootSequence {
     sequence: Vec<Step>,
 }
 
 impl RebootSequence {
-    pub fn parse(s: &str) -> Self {
-        let sequence = s.lines().map(|l| Step::from_str(l).unwrap()).collect();
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        let sequence = try_parse_lines_as(s, Step::from_str)?;
 
-        Self { sequence }
+        Ok(Self { sequence })
     }
 
-    pub fn reboot_initialization(&self) {
+    pub fn reboot_initialization(&self) -> usize {
         let mut cubes_on: HashSet<(i64, i64, i64)> = HashSet::new();
         for step in &self.sequence {
             let (s_x, s_y, s_z) = step.get_range_clamped();
@@ -132,10 +148,10 @@ impl RebootSequence {
                 }
             }
         }
-        println!("Number of cubes that are on: {}", cubes_on.len());
+        cubes_on.len()
     }
 
-    pub fn reboot_full(&self) {
+    pub fn reboot_full(&self) -> i64 {
         let mut all_steps: Vec<Step> = Vec::new();
         for step in &self.sequence {
             let mut merge = Vec::new();
@@ -153,16 +169,167 @@ impl RebootSequence {
             all_steps.append(&mut merge);
         }
 
-        let result: i64 = all_steps.iter().map(Step::calculate_volume).sum();
+        all_steps.iter().map(Step::calculate_volume).sum()
+    }
+}
 
-        println!("Number of cubes that are on: {}", result);
+/// Generates `size` reboot steps, the first twentieth of which are small cuboids in the
+/// initialization region and the rest large ones outside of it.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    fn cuboid(rng: &mut InputRng, min: i64, max: i64, sides: Range<i64>) -> [(i64, i64); 3] {
+        [(); 3].map(|_| {
+            let from = rng.gen_range(min..max);
+            (from, (from + rng.gen_range(sides.clone())).min(max))
+        })
     }
+
+    let num_of_initialization_steps = size / 20 + 1;
+    (0..size)
+        .map(|i| {
+            let [(x1, x2), (y1, y2), (z1, z2)] = if i < num_of_initialization_steps {
+                cuboid(rng, -50, 50, 10..50)
+            } else {
+                loop {
+                    let cuboid = cuboid(rng, -100_000, 100_000, 10_000..50_000);
+                    if !cuboid.iter().all(|&(from, to)| from <= 50 && to >= -50) {
+                        break cuboid;
+                    }
+                }
+            };
+            // nothing else matters until a cuboid is turned on
+            let state = if i == 0 || rng.gen_bool(0.7) {
+                "on"
+            } else {
+                "off"
+            };
+            format!(
+                "{} x={}..{},y={}..{},z={}..{}\n",
+                state, x1, x2, y1, y2, z1, z2
+            )
+        })
+        .collect()
 }
 
-fn main() {
-    let input = read_file_to_string("input/day22.txt");
-    let sequence = RebootSequence::parse(&input);
+pub struct Day22;
 
-    sequence.reboot_initialization();
-    sequence.reboot_full();
+impl Solution for Day22 {
+    type Input = RebootSequence;
+    type Answer1 = usize;
+    type Answer2 = i64;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        RebootSequence::parse(input)
+    }
+
+    fn part1(&self, sequence: &Self::Input) -> Self::Answer1 {
+        sequence.reboot_initialization()
+    }
+
+    fn part2(&self, sequence: &Self::Input) -> Self::Answer2 {
+        sequence.reboot_full()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+on x=10..12,y=10..12,z=10..12
+on x=11..13,y=11..13,z=11..13
+off x=9..11,y=9..11,z=9..11
+on x=10..10,y=10..10,z=10..10
+";
+
+    fn step(s: &str) -> Step {
+        Step::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn example_part1() {
+        let input = Day22.parse(EXAMPLE).unwrap();
+        assert_eq!(Day22.part1(&input), 39);
+    }
+
+    #[test]
+    fn example_part2() {
+        let input = Day22.parse(EXAMPLE).unwrap();
+        assert_eq!(Day22.part2(&input), 39);
+    }
+
+    #[test]
+    fn intersect_overlapping_cuboids() {
+        let a = step("on x=10..12,y=10..12,z=10..12");
+        let b = step("on x=11..13,y=11..13,z=11..13");
+        let intersection = a.intersect(&b).unwrap();
+        assert_eq!(intersection.from, (11, 11, 11));
+        assert_eq!(intersection.to, (12, 12, 12));
+        // the intersection cancels out the volume counted twice
+        assert!(!intersection.on);
+        assert_eq!(intersection.calculate_volume(), -8);
+    }
+
+    #[test]
+    fn intersect_disjoint_cuboids() {
+        let a = step("on x=0..1,y=0..1,z=0..1");
+        let b = step("off x=2..3,y=0..1,z=0..1");
+        assert!(a.intersect(&b).is_none());
+    }
+
+    #[test]
+    fn parse_rejects_unknown_state() {
+        assert!(Step::from_str("toggle x=0..1,y=0..1,z=0..1").is_err());
+    }
+
+    /// A step on a cuboid inside, outside or straddling the initialization region.
+    fn any_step() -> impl Strategy<Value = String> {
+        let range = (-80..=80i64, 0..30i64).prop_map(|(from, length)| (from, from + length));
+        (any::<bool>(), range.clone(), range.clone(), range).prop_map(|(on, x, y, z)| {
+            format!(
+                "{} x={}..{},y={}..{},z={}..{}",
+                if on { "on" } else { "off" },
+                x.0,
+                x.1,
+                y.0,
+                y.1,
+                z.0,
+                z.1
+            )
+        })
+    }
+
+    /// The steps cut down to the initialization region, dropping those entirely outside of it.
+    fn clip_to_initialization_region(sequence: &RebootSequence) -> RebootSequence {
+        let region = Step {
+            from: (-50, -50, -50),
+            to: (50, 50, 50),
+            on: true,
+        };
+        let sequence = sequence
+            .sequence
+            .iter()
+            .filter_map(|step| {
+                region.intersect(step).map(|clipped| Step {
+                    on: step.on,
+                    ..clipped
+                })
+            })
+            .collect();
+        RebootSequence { sequence }
+    }
+
+    proptest! {
+        #[test]
+        fn initialization_reboot_matches_full_reboot_of_the_region(
+            steps in prop::collection::vec(any_step(), 0..12),
+        ) {
+            let sequence = RebootSequence::parse(&steps.join("\n")).unwrap();
+            // the cube by cube initialization reboot is the brute-force oracle
+            prop_assert_eq!(
+                sequence.reboot_initialization() as i64,
+                clip_to_initialization_region(&sequence).reboot_full()
+            );
+        }
+    }
 }
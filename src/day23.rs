@@ -1,23 +1,28 @@
-use crate::Amphipod::{A, B, C, D};
-use advent_of_code_2021::{read_file_lines, vec_to_array};
+use self::Amphipod::{A, B, C, D};
+use crate::generate::InputRng;
+use crate::search::{dijkstra, Graph};
+use crate::{debug, parse_lines, vec_to_array, Error, Solution};
+use rand::seq::SliceRandom;
 use sscanf::scanf;
 
-#[derive(Debug, Copy, Clone, PartialEq)]
-enum Amphipod {
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Amphipod {
     A,
     B,
     C,
     D,
 }
 
-impl From<char> for Amphipod {
-    fn from(value: char) -> Self {
+impl TryFrom<char> for Amphipod {
+    type Error = String;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
         match value {
-            'A' => A,
-            'B' => B,
-            'C' => C,
-            'D' => D,
-            _ => panic!("Invalid Amphipod"),
+            'A' => Ok(A),
+            'B' => Ok(B),
+            'C' => Ok(C),
+            'D' => Ok(D),
+            _ => Err(format!("'{}' is not an amphipod", value)),
         }
     }
 }
@@ -53,8 +58,8 @@ fn option_amphipod_to_char(pod: &Option<Amphipod>) -> char {
     pod.map_or('.', |p| p.to_char())
 }
 
-#[derive(Debug, Clone)]
-struct Room {
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Room {
     home_for: Amphipod,
     position: usize,
     room_size: usize,
@@ -106,18 +111,28 @@ impl Room {
     }
 }
 
+fn parse_amphipods((a, b, c, d): (char, char, char, char)) -> Result<[Amphipod; 4], String> {
+    Ok([a.try_into()?, b.try_into()?, c.try_into()?, d.try_into()?])
+}
+
 fn distance_between(from: usize, to: usize) -> u32 {
     (if to > from { to - from } else { from - to }) as u32
 }
 
-#[derive(Debug, Clone)]
-struct Burrow {
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Burrow {
     hallway: [Option<Amphipod>; 11],
     rooms: [Room; 4],
 }
 
 impl Burrow {
-    pub fn new(input: &Vec<String>) -> Self {
+    pub fn parse(input: &[String]) -> Result<Self, Error> {
+        if input.len() < 5 {
+            return Err(Error::Invalid(
+                "expected a hallway and at least two rows of rooms".to_string(),
+            ));
+        }
+
         let first_room_row = 2;
         let second_roow_row = first_room_row + 1;
         let num_of_room_rows = input.len() - 3;
@@ -129,30 +144,52 @@ impl Burrow {
         let mut vec_c: Vec<Amphipod> = Vec::with_capacity(num_of_room_rows);
         let mut vec_d: Vec<Amphipod> = Vec::with_capacity(num_of_room_rows);
 
-        for r in room_rows_excluding_first.iter().rev() {
-            let (a, b, c, d) = scanf!(r, "  #{}#{}#{}#{}#", char, char, char, char).unwrap();
-            vec_a.push(a.into());
-            vec_b.push(b.into());
-            vec_c.push(c.into());
-            vec_d.push(d.into());
+        for (i, r) in room_rows_excluding_first.iter().enumerate().rev() {
+            let line = second_roow_row + i + 1;
+            let [a, b, c, d] = scanf!(r, "  #{}#{}#{}#{}#", char, char, char, char)
+                .ok_or_else(|| format!("'{}' is not a row of rooms", r))
+                .and_then(parse_amphipods)
+                .map_err(|e| Error::malformed(line, e))?;
+            vec_a.push(a);
+            vec_b.push(b);
+            vec_c.push(c);
+            vec_d.push(d);
         }
 
-        let (a, b, c, d) = scanf!(
-            input[first_room_row],
-            "###{}#{}#{}#{}###",
-            char,
-            char,
-            char,
-            char
-        )
-        .unwrap();
-
-        vec_a.push(a.into());
-        vec_b.push(b.into());
-        vec_c.push(c.into());
-        vec_d.push(d.into());
+        let first_row = &input[first_room_row];
+        let [a, b, c, d] = scanf!(first_row, "###{}#{}#{}#{}###", char, char, char, char)
+            .ok_or_else(|| format!("'{}' is not a row of rooms", first_row))
+            .and_then(parse_amphipods)
+            .map_err(|e| Error::malformed(first_room_row + 1, e))?;
 
-        Self {
+        vec_a.push(a);
+        vec_b.push(b);
+        vec_c.push(c);
+        vec_d.push(d);
+
+        Self::from_rooms([vec_a, vec_b, vec_c, vec_d])
+    }
+
+    pub fn from_rooms([vec_a, vec_b, vec_c, vec_d]: [Vec<Amphipod>; 4]) -> Result<Self, Error> {
+        // every amphipod needs exactly one free spot in its own room, otherwise it is unsolvable
+        let all = [&vec_a, &vec_b, &vec_c, &vec_d];
+        for amphipod in [A, B, C, D] {
+            let count = all
+                .iter()
+                .flat_map(|v| v.iter())
+                .filter(|&&a| a == amphipod)
+                .count();
+            if count != vec_a.len() {
+                return Err(Error::Invalid(format!(
+                    "expected {} amphipods of type {}, found {}",
+                    vec_a.len(),
+                    amphipod,
+                    count
+                )));
+            }
+        }
+
+        Ok(Self {
             hallway: [None; 11],
             rooms: [
                 Room::new(A, 2, vec_a),
@@ -160,91 +197,66 @@ impl Burrow {
                 Room::new(C, 6, vec_c),
                 Room::new(D, 8, vec_d),
             ],
-        }
+        })
     }
 
-    #[allow(dead_code)]
-    pub fn print(&self) {
-        println!("#############");
-        let hallway = self
-            .hallway
-            .iter()
-            .map(|s| option_amphipod_to_char(&s))
-            .collect::<String>();
-
-        println!("#{hallway}#");
-
-        let map_row_to_chars = |row_idx: usize| -> [char; 4] {
-            vec_to_array(
-                self.rooms
-                    .iter()
-                    .map(|r| option_amphipod_to_char(&r.spots.get(row_idx).copied()))
-                    .collect(),
-            )
-        };
-
-        let room_sizes = self.rooms[0].room_size;
-
-        let top_chars = map_row_to_chars(room_sizes - 1);
-        println!(
-            "###{}#{}#{}#{}###",
-            top_chars[0], top_chars[1], top_chars[2], top_chars[3],
+    /// Inserts the two rows hidden by the folded part of the diagram right below the first row:
+    /// ```text
+    ///   #D#C#B#A#
+    ///   #D#B#A#C#
+    /// ```
+    pub fn unfold(&self) -> Self {
+        let hidden_top = [D, C, B, A];
+        let hidden_bottom = [D, B, A, C];
+        let rooms = vec_to_array(
+            self.rooms
+                .iter()
+                .enumerate()
+                .map(|(i, room)| {
+                    // spots are stored from the bottom of the room to its top, so the hidden
+                    // rows go right below the last one
+                    let mut spots = room.spots.clone();
+                    let top = spots.pop().unwrap();
+                    spots.extend([hidden_bottom[i], hidden_top[i], top]);
+                    spots
+                })
+                .collect(),
         );
-        for i in (0..(room_sizes - 1)).rev() {
-            let chars = map_row_to_chars(i);
-            println!("  #{}#{}#{}#{}#", chars[0], chars[1], chars[2], chars[3],);
-        }
 
-        println!("  #########");
-        println!();
+        Self::from_rooms(rooms).unwrap()
     }
 
-    pub fn solve(&mut self) -> Option<u32> {
-        let mut cost = 0u32;
-        loop {
-            let mut greedy = self
-                .try_move_an_amphipod_in_hallway_to_respective_room()
-                .unwrap_or(0);
-            greedy += self
-                .try_move_an_amphipod_in_a_wrong_room_to_respective_room()
-                .unwrap_or(0);
-
-            if greedy == 0 {
-                break;
-            }
-
-            cost += greedy;
-        }
+    /// Finds the least energy needed to organize the amphipods, if they can be organized at all.
+    pub fn solve(&self) -> Option<u32> {
+        debug!("Organizing the amphipods of\n{}", self);
+        dijkstra(&AmphipodMoves, self.clone(), Burrow::is_solved).map(|path| path.cost as u32)
+    }
 
-        if self.is_solved() {
-            return Some(cost);
+    /// Every burrow reachable by moving a single amphipod, along with the energy it takes.
+    fn moves(&self) -> Vec<(Burrow, usize)> {
+        // moving an amphipod into its own room never makes the solution more expensive, so if one
+        // can go there, no other move needs to be considered
+        let mut next = self.clone();
+        if let Some(cost) = next
+            .try_move_an_amphipod_in_hallway_to_respective_room()
+            .or_else(|| next.try_move_an_amphipod_in_a_wrong_room_to_respective_room())
+        {
+            return vec![(next, cost as usize)];
         }
 
-        let mut best = u32::MAX;
+        let mut moves = Vec::new();
         for room_idx in 0..4 {
-            if self.rooms[room_idx].is_empty() {
-                continue;
-            }
-
             for space_idx in 0..11 {
-                let mut copy = self.clone();
-                if let Some(move_cost) =
-                    copy.try_move_an_amphipod_from_a_wrong_room_to_hallway(room_idx, space_idx)
+                let mut next = self.clone();
+                if let Some(cost) =
+                    next.try_move_an_amphipod_from_a_wrong_room_to_hallway(room_idx, space_idx)
                 {
-                    let rec_result = copy.solve();
-                    if rec_result.is_none() {
-                        continue;
-                    }
-                    best = best.min(move_cost + rec_result.unwrap());
+                    moves.push((next, cost as usize));
                 }
             }
         }
 
-        if best == u32::MAX {
-            return None;
-        }
-
-        return Some(best + cost);
+        moves
     }
 
     fn is_solved(&self) -> bool {
@@ -260,11 +272,15 @@ impl Burrow {
         }
     }
 
+    /// Checks the hallway between an amphipod standing at `from` and `to`, not counting itself.
     fn is_path_free_between(&self, from: usize, to: usize) -> bool {
-        let i = from.min(to);
-        let j = from.max(to);
+        let path = if from < to {
+            &self.hallway[(from + 1)..=to]
+        } else {
+            &self.hallway[to..from]
+        };
 
-        self.hallway[i..j].iter().filter(|s| s.is_some()).count() <= 1
+        path.iter().all(|s| s.is_none())
     }
 
     fn is_path_free_between_inclusive(&self, from: usize, to: usize) -> bool {
@@ -350,27 +366,148 @@ impl Burrow {
     }
 }
 
-fn solve_first(input: &Vec<String>) {
-    let min_total_energy = Burrow::new(input).solve().unwrap();
-    println!(
-        "The minimum energy required for ampipods to organize in the original input is {}",
-        min_total_energy
-    );
+impl std::fmt::Display for Burrow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let hallway = self
+            .hallway
+            .iter()
+            .map(option_amphipod_to_char)
+            .collect::<String>();
+
+        writeln!(f, "#############")?;
+        writeln!(f, "#{hallway}#")?;
+
+        let map_row_to_chars = |row_idx: usize| -> [char; 4] {
+            vec_to_array(
+                self.rooms
+                    .iter()
+                    .map(|r| option_amphipod_to_char(&r.spots.get(row_idx).copied()))
+                    .collect(),
+            )
+        };
+
+        let room_sizes = self.rooms[0].room_size;
+
+        let top_chars = map_row_to_chars(room_sizes - 1);
+        writeln!(
+            f,
+            "###{}#{}#{}#{}###",
+            top_chars[0], top_chars[1], top_chars[2], top_chars[3],
+        )?;
+        for i in (0..(room_sizes - 1)).rev() {
+            let chars = map_row_to_chars(i);
+            writeln!(f, "  #{}#{}#{}#{}#", chars[0], chars[1], chars[2], chars[3])?;
+        }
+
+        write!(f, "  #########")
+    }
 }
 
-fn solve_second(input: &Vec<String>) {
-    let mut input = input.clone();
-    input.insert(3, "  #D#C#B#A#".to_string());
-    input.insert(4, "  #D#B#A#C#".to_string());
-    let min_total_energy = Burrow::new(&input).solve().unwrap();
-    println!(
-        "The minimum energy required for ampipods to organize in the extended input is {}",
-        min_total_energy
-    );
+/// The moves of the amphipods, taking a burrow from one arrangement to another.
+pub struct AmphipodMoves;
+
+impl Graph for AmphipodMoves {
+    type State = Burrow;
+
+    fn neighbours(&self, burrow: &Burrow) -> Vec<(Burrow, usize)> {
+        burrow.moves()
+    }
+}
+
+fn solve_first(burrow: &Burrow) -> u32 {
+    burrow.solve().unwrap()
+}
+
+fn solve_second(burrow: &Burrow) -> u32 {
+    burrow.unfold().solve().unwrap()
+}
+
+/// Generates a burrow with side rooms `size` deep, but at least 2 like the real one, and the
+/// amphipods shuffled among them.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    let mut amphipods = [A, B, C, D]
+        .iter()
+        .flat_map(|&a| vec![a; size.max(2)])
+        .collect::<Vec<_>>();
+    amphipods.shuffle(rng);
+
+    let mut lines = vec!["#############".to_string(), "#...........#".to_string()];
+    for (i, row) in amphipods.chunks(4).enumerate() {
+        let wall = if i == 0 { "##" } else { "  " };
+        let row = row.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        lines.push(format!("{}#{}#{}", wall, row.join("#"), wall.trim()));
+    }
+    lines.push("  #########".to_string());
+
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+pub struct Day23;
+
+impl Solution for Day23 {
+    type Input = Burrow;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Burrow::parse(&parse_lines(input))
+    }
+
+    fn part1(&self, burrow: &Self::Input) -> Self::Answer1 {
+        solve_first(burrow)
+    }
+
+    fn part2(&self, burrow: &Self::Input) -> Self::Answer2 {
+        solve_second(burrow)
+    }
 }
 
-fn main() {
-    let input = read_file_lines("input/day23.txt");
-    solve_first(&input);
-    solve_second(&input);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = concat!(
+        "#############\n",
+        "#...........#\n",
+        "###B#C#B#D###\n",
+        "  #A#D#C#A#\n",
+        "  #########\n",
+    );
+
+    #[test]
+    fn example_part1() {
+        let input = Day23.parse(EXAMPLE).unwrap();
+        assert_eq!(Day23.part1(&input), 12521);
+    }
+
+    #[test]
+    fn example_part2() {
+        let input = Day23.parse(EXAMPLE).unwrap();
+        assert_eq!(Day23.part2(&input), 44169);
+    }
+
+    #[test]
+    fn unfold_inserts_hidden_rows() {
+        let burrow = Day23.parse(EXAMPLE).unwrap().unfold();
+        let spots = burrow
+            .rooms
+            .iter()
+            .map(|r| r.spots.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            spots,
+            vec![
+                vec![A, D, D, B],
+                vec![D, B, C, C],
+                vec![C, A, B, B],
+                vec![A, C, A, D]
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_rejects_missing_amphipods() {
+        let input = EXAMPLE.replace("#A#D#C#A#", "#A#D#C#B#");
+        assert!(Day23.parse(&input).is_err());
+    }
 }
@@ -1,3 +1,7 @@
+use crate::generate::InputRng;
+use crate::{Error, Solution};
+use rand::Rng;
+
 /*
     w_i: current digit
     optype: op_i: 1st div in block: `div z <op>`, can be `1` or `26`
@@ -102,14 +106,109 @@ fn find_smallest_number_accepted_by_monad() -> u64 {
     11717131211195
 }
 
-fn main() {
-    println!(
-        "The largest number accepted by MONAD is {}",
+const BLOCK: &str = "\
+inp w
+mul x 0
+add x z
+mod x 26
+div z {op}
+add x {corr}
+eql x w
+eql x 0
+mul y 0
+add y 25
+mul y x
+add y 1
+mul z y
+mul y 0
+add y w
+add y {off}
+mul y x
+add z y
+";
+
+/// Generates a MONAD of `2 * size` blocks, pairing up every push with a later pop as above. The
+/// digits of each pair may differ by up to 8, so some model number is always accepted.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    let mut pushed_offsets = Vec::new();
+    let mut pushes_left = size;
+    (0..2 * size)
+        .map(|_| {
+            let push = pushes_left > 0 && (pushed_offsets.is_empty() || rng.gen_bool(0.5));
+            let offset = rng.gen_range(1..=16);
+            let (optype, correction) = if push {
+                pushes_left -= 1;
+                pushed_offsets.push(offset);
+                // never equal to a digit, so a push always pushes
+                (1, rng.gen_range(10..=16))
+            } else {
+                let pushed_offset = pushed_offsets.pop().unwrap();
+                (26, rng.gen_range(-8..=8) - pushed_offset)
+            };
+            BLOCK
+                .replace("{op}", &optype.to_string())
+                .replace("{corr}", &correction.to_string())
+                .replace("{off}", &offset.to_string())
+        })
+        .collect()
+}
+
+pub struct Day24;
+
+impl Solution for Day24 {
+    // the solution was worked out by hand from the input, see above
+    type Input = ();
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(&self, _input: &str) -> Result<Self::Input, Error> {
+        Ok(())
+    }
+
+    fn part1(&self, _input: &Self::Input) -> Self::Answer1 {
         find_largest_number_accepted_by_monad()
-    );
+    }
 
-    println!(
-        "The smallest number accepted by MONAD is {}",
+    fn part2(&self, _input: &Self::Input) -> Self::Answer2 {
         find_smallest_number_accepted_by_monad()
-    );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the pairs of digits and their differences worked out above
+    const CONSTRAINTS: [(usize, usize, i32); 7] = [
+        (1, 14, 4),
+        (2, 13, 8),
+        (3, 6, -6),
+        (4, 5, 6),
+        (7, 8, -2),
+        (9, 10, -1),
+        (11, 12, 0),
+    ];
+
+    fn satisfies_constraints(number: u64) -> bool {
+        let digits = number
+            .to_string()
+            .chars()
+            .map(|c| c.to_digit(10).unwrap() as i32)
+            .collect::<Vec<_>>();
+        digits.len() == 14
+            && digits.iter().all(|&w| (1..=9).contains(&w))
+            && CONSTRAINTS
+                .iter()
+                .all(|&(i, j, diff)| digits[i - 1] + diff == digits[j - 1])
+    }
+
+    #[test]
+    fn answers_satisfy_constraints() {
+        assert!(satisfies_constraints(
+            find_largest_number_accepted_by_monad()
+        ));
+        assert!(satisfies_constraints(
+            find_smallest_number_accepted_by_monad()
+        ));
+    }
 }
@@ -1,38 +1,42 @@
-use advent_of_code_2021::read_file_to_string;
-use array2d::Array2D;
+use crate::generate::InputRng;
+use crate::{
+    render::{Animation, Frame, Palette},
+    Coords, Error, Grid, Solution,
+};
+use rand::seq::SliceRandom;
+
+const EAST: (isize, isize) = (1, 0);
+const SOUTH: (isize, isize) = (0, 1);
 
 #[derive(Clone, Eq, PartialEq)]
-enum Tile {
+pub enum Tile {
     Empty,
     East,
     South,
 }
 
 impl Tile {
-    pub fn from_char(c: char) -> Self {
+    pub fn from_char(c: char) -> Result<Self, String> {
         match c {
-            '.' => Self::Empty,
-            '>' => Self::East,
-            'v' => Self::South,
-            _ => unreachable!(),
+            '.' => Ok(Self::Empty),
+            '>' => Ok(Self::East),
+            'v' => Ok(Self::South),
+            _ => Err(format!("'{}' is neither a sea cucumber nor empty", c)),
         }
     }
 }
 
-struct OceanFloor {
-    tiles: Array2D<Tile>,
+#[derive(Clone)]
+pub struct OceanFloor {
+    tiles: Grid<Tile>,
 }
 
 impl OceanFloor {
-    pub fn parse(s: &str) -> Self {
-        let tiles = &*s
-            .lines()
-            .map(|l| l.chars().map(|c| Tile::from_char(c)).collect::<Vec<_>>())
-            .collect::<Vec<_>>();
-
-        Self {
-            tiles: Array2D::from_rows(tiles).unwrap(),
-        }
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        // the sea cucumbers that move off an edge reappear on the opposite one
+        Ok(Self {
+            tiles: Grid::parse(s, Tile::from_char)?.wrapping(),
+        })
     }
 
     pub fn step_until_stuck(&mut self) -> usize {
@@ -46,35 +50,19 @@ impl OceanFloor {
     fn step(&mut self) -> bool {
         let (east_herd, south_herd) = self.get_east_and_south_herds();
 
-        let movable_east_herd = east_herd
-            .into_iter()
-            .filter(|&(x, y)| self.is_east_neighbour_free(x, y))
-            .collect::<Vec<_>>();
-
-        for &(x, y) in &movable_east_herd {
-            self.move_east(x, y);
-        }
+        let moved_east = self.move_herd(east_herd, Tile::East, EAST);
+        let moved_south = self.move_herd(south_herd, Tile::South, SOUTH);
 
-        let movable_south_herd = south_herd
-            .into_iter()
-            .filter(|&(x, y)| self.is_south_neighbour_free(x, y))
-            .collect::<Vec<_>>();
-
-        for &(x, y) in &movable_south_herd {
-            self.move_south(x, y);
-        }
-
-        movable_east_herd.is_empty() && movable_south_herd.is_empty()
+        !moved_east && !moved_south
     }
 
-    fn get_east_and_south_herds(&self) -> (Vec<(usize, usize)>, Vec<(usize, usize)>) {
+    fn get_east_and_south_herds(&self) -> (Vec<Coords>, Vec<Coords>) {
         let mut east_herd = Vec::new();
         let mut south_herd = Vec::new();
-        for (y, x) in self.tiles.indices_row_major() {
-            let tile = self.tiles.get(y, x).unwrap();
+        for (coords, tile) in self.tiles.iter() {
             match tile {
-                Tile::East => east_herd.push((x, y)),
-                Tile::South => south_herd.push((x, y)),
+                Tile::East => east_herd.push(coords),
+                Tile::South => south_herd.push(coords),
                 _ => {}
             }
         }
@@ -82,63 +70,123 @@ impl OceanFloor {
         (east_herd, south_herd)
     }
 
-    fn move_east(&mut self, x: usize, y: usize) {
-        let (n_x, n_y) = self.east_neighbour_pos(x, y);
-        self.tiles.set(y, x, Tile::Empty).unwrap();
-        self.tiles.set(n_y, n_x, Tile::East).unwrap();
-    }
+    /// Moves every sea cucumber of the herd that faces an empty tile at the same time, returning
+    /// whether any of them could move.
+    fn move_herd(&mut self, herd: Vec<Coords>, tile: Tile, direction: (isize, isize)) -> bool {
+        let movable_herd = herd
+            .into_iter()
+            .filter_map(|coords| {
+                let neighbour = self.tiles.offset(coords, direction).unwrap();
+                (self.tiles[neighbour] == Tile::Empty).then_some((coords, neighbour))
+            })
+            .collect::<Vec<_>>();
 
-    fn is_east_neighbour_free(&self, x: usize, y: usize) -> bool {
-        *self.east_neighbour(x, y) == Tile::Empty
-    }
+        for &(coords, neighbour) in &movable_herd {
+            self.tiles.set(coords, Tile::Empty);
+            self.tiles.set(neighbour, tile.clone());
+        }
 
-    fn east_neighbour(&self, x: usize, y: usize) -> &Tile {
-        let (n_x, n_y) = self.east_neighbour_pos(x, y);
-        self.tiles.get(n_y, n_x).unwrap()
+        !movable_herd.is_empty()
     }
+}
 
-    fn east_neighbour_pos(&self, x: usize, y: usize) -> (usize, usize) {
-        let n_x = if x == self.tiles.num_columns() - 1 {
-            0
-        } else {
-            x + 1
-        };
-        (n_x, y)
+/// Renders every step until the sea cucumbers stop moving, with the east-facing herd in orange and
+/// the south-facing one in green.
+pub fn animate(ocean_floor: &OceanFloor) -> Animation {
+    let mut ocean_floor = ocean_floor.clone();
+    let render = |ocean_floor: &OceanFloor| {
+        Frame::from_grid(&ocean_floor.tiles, |tile| match tile {
+            Tile::Empty => 0,
+            Tile::East => 1,
+            Tile::South => 2,
+        })
+    };
+
+    let mut frames = vec![render(&ocean_floor)];
+    loop {
+        let stuck = ocean_floor.step();
+        frames.push(render(&ocean_floor));
+        if stuck {
+            break;
+        }
     }
 
-    fn move_south(&mut self, x: usize, y: usize) {
-        let (n_x, n_y) = self.south_neighbour_pos(x, y);
-        self.tiles.set(y, x, Tile::Empty).unwrap();
-        self.tiles.set(n_y, n_x, Tile::South).unwrap();
+    Animation {
+        frames,
+        palette: Palette::new(vec![[0, 30, 70], [255, 140, 0], [100, 220, 120]]),
+        scale: 4,
     }
+}
+
+/// Generates a `size`x`size` ocean floor, with each herd covering about a quarter of it.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let row = (0..size).map(|_| *['.', '.', '>', 'v'].choose(rng).unwrap());
+            row.collect::<String>() + "\n"
+        })
+        .collect()
+}
+
+pub struct Day25;
+
+impl Solution for Day25 {
+    type Input = OceanFloor;
+    type Answer1 = usize;
+    type Answer2 = &'static str;
+
+    const HAS_PART2: bool = false;
 
-    fn is_south_neighbour_free(&self, x: usize, y: usize) -> bool {
-        *self.south_neighbour(x, y) == Tile::Empty
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        OceanFloor::parse(input)
     }
 
-    fn south_neighbour(&self, x: usize, y: usize) -> &Tile {
-        let (n_x, n_y) = self.south_neighbour_pos(x, y);
-        self.tiles.get(n_y, n_x).unwrap()
+    fn part1(&self, ocean_floor: &Self::Input) -> Self::Answer1 {
+        ocean_floor.clone().step_until_stuck()
     }
 
-    fn south_neighbour_pos(&self, x: usize, y: usize) -> (usize, usize) {
-        let n_y = if y == self.tiles.num_rows() - 1 {
-            0
-        } else {
-            y + 1
-        };
-        (x, n_y)
+    fn part2(&self, _ocean_floor: &Self::Input) -> Self::Answer2 {
+        "Day 25 has no second part"
     }
 }
 
-fn main() {
-    let input = read_file_to_string("input/day25.txt");
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+v...>>.vv>
+.vv>>.vv..
+>>.>v>...v
+>>v>>.>.v.
+v>v.vv.v..
+>.>>..v...
+.vv..>.>v.
+v.v..>>v.v
+....v..v.>
+";
+
+    #[test]
+    fn example_part1() {
+        let input = Day25.parse(EXAMPLE).unwrap();
+        assert_eq!(Day25.part1(&input), 58);
+    }
 
-    let mut ocean_floor = OceanFloor::parse(&input);
+    #[test]
+    fn animation_has_a_frame_per_step() {
+        let input = Day25.parse(EXAMPLE).unwrap();
+        let animation = animate(&input);
+        // the initial state, and the state after each of the 58 steps
+        assert_eq!(animation.frames.len(), 59);
+        assert_eq!(animation.frames[0].pixel((0, 0)), 2);
+        assert_eq!(animation.frames[0].pixel((4, 0)), 1);
+    }
 
-    let result = ocean_floor.step_until_stuck();
-    println!(
-        "The first step on which no sea cucumbers move is step {}",
-        result
-    );
+    #[test]
+    fn herds_wrap_around() {
+        let mut ocean_floor = OceanFloor::parse("...>>\n").unwrap();
+        ocean_floor.step();
+        assert!(ocean_floor.tiles[(0, 0)] == Tile::East);
+        assert!(ocean_floor.tiles[(3, 0)] == Tile::East);
+    }
 }
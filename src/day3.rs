@@ -1,4 +1,7 @@
-use advent_of_code_2021::read_file_lines_as;
+use crate::generate::InputRng;
+use crate::{info, try_parse_lines_as, Error, LineError, Solution};
+use rand::Rng;
+use std::collections::HashSet;
 
 fn calculate_most_common_bits_in_all_indices(reports: &Vec<Vec<u8>>) -> Vec<u8> {
     let report_len = reports.first().unwrap().len();
@@ -10,25 +13,24 @@ fn calculate_most_common_bits_in_all_indices(reports: &Vec<Vec<u8>>) -> Vec<u8>
         }
     }
 
-    let half = reports.len() / 2;
     num_of_ones
         .iter()
-        .map(|n| most_common_bit(*n, half))
+        .map(|n| most_common_bit(*n, reports.len()))
         .collect()
 }
 
-fn most_common_bit(n_ones: u32, half: usize) -> u8 {
-    if n_ones == half as u32 {
-        1 // if both digits are equally common, return 1
-    } else if n_ones > half as u32 {
+fn most_common_bit(n_ones: u32, num_of_values: usize) -> u8 {
+    // compare against the whole count, as halving an odd count would round down; if both digits
+    // are equally common, return 1
+    if 2 * n_ones as usize >= num_of_values {
         1u8
     } else {
         0u8
     }
 }
 
-fn least_common_bit(n_ones: u32, half: usize) -> u8 {
-    invert(most_common_bit(n_ones, half))
+fn least_common_bit(n_ones: u32, num_of_values: usize) -> u8 {
+    invert(most_common_bit(n_ones, num_of_values))
 }
 
 fn bits_to_u32(bits: &Vec<u8>) -> u32 {
@@ -56,7 +58,7 @@ fn calculate_power_consumption(reports: &Vec<Vec<u8>>) -> u32 {
     let gamma = bits_to_u32(&most_common_bits);
     let epsilon = bits_to_u32(&least_common_bits);
 
-    println!("Gamma: {}, Epsilon: {}", gamma, epsilon);
+    info!("Gamma: {}, Epsilon: {}", gamma, epsilon);
     power_consumption(gamma, epsilon)
 }
 
@@ -66,12 +68,12 @@ fn get_num_of_ones_at_index(values: &Vec<Vec<u8>>, i: usize) -> u32 {
 
 fn calculate_most_common_bit_at_index(values: &Vec<Vec<u8>>, i: usize) -> u8 {
     let num_of_ones = get_num_of_ones_at_index(&values, i);
-    most_common_bit(num_of_ones, values.len() / 2)
+    most_common_bit(num_of_ones, values.len())
 }
 
 fn calculate_least_common_bit_at_index(values: &Vec<Vec<u8>>, i: usize) -> u8 {
     let num_of_ones = get_num_of_ones_at_index(&values, i);
-    least_common_bit(num_of_ones, values.len() / 2)
+    least_common_bit(num_of_ones, values.len())
 }
 
 fn filter_on_bits_until_one_left(
@@ -109,23 +111,108 @@ fn calculate_life_support_rating(reports: &Vec<Vec<u8>>) -> u32 {
     let o2_generator_rating = calculate_o2_generator_rating(&reports);
     let co2_scrubber_rating = calculate_co2_scrubber_rating(&reports);
 
-    println!(
+    info!(
         "O2 generator rating: {}, CO2 scrubber rating: {}",
         o2_generator_rating, co2_scrubber_rating
     );
     life_support_rating(o2_generator_rating, co2_scrubber_rating)
 }
 
-fn main() {
-    let reports = read_file_lines_as("input/day3.txt", |s| {
-        s.chars()
-            .map(|digit| digit.to_digit(2).unwrap() as u8)
-            .collect::<Vec<_>>()
-    });
+fn parse_report(s: &str) -> Result<Vec<u8>, LineError> {
+    s.chars()
+        .enumerate()
+        .map(|(i, digit)| {
+            digit
+                .to_digit(2)
+                .map(|d| d as u8)
+                .ok_or_else(|| LineError::at(i + 1, format!("'{}' is not a bit", digit)))
+        })
+        .collect()
+}
+
+/// Generates `size` distinct reports of at least 12 bits; they have to be distinct for the ratings
+/// to narrow down to a single report.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    let width = (usize::BITS - size.leading_zeros() + 1).max(12) as usize;
+    let mut seen = HashSet::new();
+    let mut input = String::new();
+    while seen.len() < size {
+        let report = rng.gen_range(0..1u64 << width);
+        if seen.insert(report) {
+            input.push_str(&format!("{:0width$b}\n", report, width = width));
+        }
+    }
+
+    input
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    type Input = Vec<Vec<u8>>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        try_parse_lines_as(input, parse_report)
+    }
+
+    fn part1(&self, reports: &Self::Input) -> Self::Answer1 {
+        calculate_power_consumption(reports)
+    }
+
+    fn part2(&self, reports: &Self::Input) -> Self::Answer2 {
+        calculate_life_support_rating(reports)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+00100
+11110
+10110
+10111
+10101
+01111
+00111
+11100
+10000
+11001
+00010
+01010
+";
+
+    #[test]
+    fn example_part1() {
+        let input = Day3.parse(EXAMPLE).unwrap();
+        assert_eq!(Day3.part1(&input), 198);
+    }
 
-    let power_consumption = calculate_power_consumption(&reports);
-    println!("Power consumption is: {}", power_consumption);
+    #[test]
+    fn example_part2() {
+        let input = Day3.parse(EXAMPLE).unwrap();
+        assert_eq!(Day3.part2(&input), 230);
+    }
+
+    #[test]
+    fn ratings_of_example() {
+        let reports = Day3.parse(EXAMPLE).unwrap();
+        assert_eq!(calculate_o2_generator_rating(&reports), 23);
+        assert_eq!(calculate_co2_scrubber_rating(&reports), 10);
+    }
 
-    let life_support_rating = calculate_life_support_rating(&reports);
-    println!("Life support rating is: {}", life_support_rating);
+    #[test]
+    fn ties_count_as_ones() {
+        assert_eq!(most_common_bit(2, 4), 1);
+        assert_eq!(least_common_bit(2, 4), 0);
+    }
+
+    #[test]
+    fn minority_of_odd_count_is_not_most_common() {
+        assert_eq!(most_common_bit(3, 7), 0);
+        assert_eq!(most_common_bit(4, 7), 1);
+    }
 }
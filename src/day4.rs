@@ -1,8 +1,10 @@
-use advent_of_code_2021::read_file_lines_extract_first;
+use crate::generate::InputRng;
+use crate::{info, split_blocks, try_parse_separated_as, Error, LineError, Solution};
 use array2d::Array2D;
+use rand::seq::SliceRandom;
 
 #[derive(Clone, Copy, Debug)]
-struct Field {
+pub struct Field {
     value: u8,
     drawn: bool,
 }
@@ -24,22 +26,38 @@ impl Field {
 }
 
 #[derive(Debug, Clone)]
-struct Board {
+pub struct Board {
     board: Array2D<Field>,
     won: bool,
 }
 
 impl Board {
-    pub fn parse(lines: &[String]) -> Self {
-        let nums_iter = lines
-            .iter()
-            .map(|line| split_str_to_u8(line))
-            .flatten()
-            .map(Field::new);
+    /// Parses the `5` rows of a board.
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        let rows = s.lines().collect::<Vec<_>>();
+        if rows.len() != 5 {
+            return Err(Error::malformed(
+                1,
+                format!("expected a board of 5 rows, found {}", rows.len()),
+            ));
+        }
+
+        let mut nums = Vec::with_capacity(25);
+        for (i, row) in rows.into_iter().enumerate() {
+            let line = i + 1;
+            let row_nums = split_str_to_u8(row).map_err(|e| Error::malformed(line, e))?;
+            if row_nums.len() != 5 {
+                return Err(Error::malformed(
+                    line,
+                    format!("expected 5 numbers in a row, found {}", row_nums.len()),
+                ));
+            }
+            nums.extend(row_nums.into_iter().map(Field::new));
+        }
 
-        let board = Array2D::from_iter_row_major(nums_iter, 5, 5).unwrap();
+        let board = Array2D::from_iter_row_major(nums.into_iter(), 5, 5).unwrap();
 
-        Self { board, won: false }
+        Ok(Self { board, won: false })
     }
 
     pub fn draw(&mut self, num: u8) -> Option<u32> {
@@ -78,7 +96,7 @@ impl Board {
 
     fn score(&self, last_num: u8) -> u32 {
         let sum_unmarked = self.sum_unmarked();
-        println!(
+        info!(
             "Sum of unmarked fields: {}, last number drawn: {}",
             sum_unmarked, last_num
         );
@@ -96,16 +114,17 @@ impl Board {
     }
 }
 
-fn str_to_u8(num: &str) -> u8 {
-    num.parse().expect(&*format!("The input was: {}", num))
+fn str_to_u8(num: &str) -> Result<u8, String> {
+    num.parse()
+        .map_err(|_| format!("'{}' is not a valid bingo number", num))
 }
 
-fn split_str_to_u8(line: &str) -> Vec<u8> {
+fn split_str_to_u8(line: &str) -> Result<Vec<u8>, String> {
     line.split_whitespace().map(str_to_u8).collect()
 }
 
-fn parse_draws(line: &str) -> Vec<u8> {
-    line.split(',').map(str_to_u8).collect()
+fn parse_draws(line: &str) -> Result<Vec<u8>, LineError> {
+    try_parse_separated_as(line, ',', str_to_u8)
 }
 
 fn find_score_of_first_winning_board(
@@ -137,22 +156,133 @@ fn find_score_of_last_winning_board(mut boards: Vec<Board>, nums: &Vec<u8>) -> R
     final_score.ok_or("No boards won after drawing all numbers.".to_string())
 }
 
-fn parse_file(path: &str) -> (Vec<u8>, Vec<Board>) {
-    let (draws, boards_raw) = read_file_lines_extract_first(path);
-    let draws = parse_draws(&draws);
-    let boards = boards_raw
-        .chunks_exact(5)
-        .map(Board::parse)
-        .collect::<Vec<_>>();
+/// Generates `size` boards and draws every number they use, so every board wins eventually.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    let mut numbers = (0..100).collect::<Vec<u8>>();
+    numbers.shuffle(rng);
+    let mut input = numbers
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    input.push('\n');
+
+    for _ in 0..size {
+        let board = numbers.choose_multiple(rng, 25).collect::<Vec<_>>();
+        input.push('\n');
+        for row in board.chunks(5) {
+            let row = row.iter().map(|n| format!("{:>2}", n)).collect::<Vec<_>>();
+            input.push_str(&row.join(" "));
+            input.push('\n');
+        }
+    }
+
+    input
+}
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    type Input = (Vec<u8>, Vec<Board>);
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        let blocks = split_blocks(input);
+        let (draws_block, boards_blocks) = blocks.split_first().ok_or(Error::EmptyInput)?;
+        let draws = parse_draws(draws_block.single_line()?)
+            .map_err(|e| Error::malformed(draws_block.first_line, e))?;
+
+        let boards = boards_blocks
+            .iter()
+            .map(|block| block.parse_with(Board::parse))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // both parts need a board that wins, and if any board does, there is a first and a last one
+        if find_score_of_first_winning_board(boards.clone(), &draws).is_err() {
+            return Err(Error::Invalid(
+                "no board wins with the numbers drawn".to_string(),
+            ));
+        }
+
+        Ok((draws, boards))
+    }
+
+    fn part1(&self, (draws, boards): &Self::Input) -> Self::Answer1 {
+        find_score_of_first_winning_board(boards.clone(), draws)
+            .expect("parsing checks a board wins")
+    }
 
-    (draws, boards)
+    fn part2(&self, (draws, boards): &Self::Input) -> Self::Answer2 {
+        find_score_of_last_winning_board(boards.clone(), draws)
+            .expect("parsing checks a board wins")
+    }
 }
 
-fn main() {
-    let (draws, boards) = parse_file("input/day4.txt");
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7
+";
+
+    #[test]
+    fn example_part1() {
+        let input = Day4.parse(EXAMPLE).unwrap();
+        assert_eq!(Day4.part1(&input), 4512);
+    }
+
+    #[test]
+    fn example_part2() {
+        let input = Day4.parse(EXAMPLE).unwrap();
+        assert_eq!(Day4.part2(&input), 1924);
+    }
 
-    let score_first = find_score_of_first_winning_board(boards.clone(), &draws).unwrap();
-    println!("The score of the first winning board is {}", score_first);
-    let score_last = find_score_of_last_winning_board(boards.clone(), &draws).unwrap();
-    println!("The score of the last winning board is {}", score_last);
+    #[test]
+    fn parse_reports_line_of_short_board_row() {
+        let board = "1 2 3 4 5\n1 2 3 4\n1 2 3 4 5\n1 2 3 4 5\n1 2 3 4 5\n";
+        let e = Day4.parse(&format!("1,2\n\n{}", board)).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 4: expected 5 numbers in a row, found 4"
+        );
+    }
+
+    #[test]
+    fn parse_rejects_draws_no_board_wins_with() {
+        let input = EXAMPLE.replacen(
+            "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1",
+            "7,4,9",
+            1,
+        );
+        assert_eq!(
+            Day4.parse(&input).unwrap_err().to_string(),
+            "invalid input: no board wins with the numbers drawn"
+        );
+        assert!(Day4
+            .parse(
+                "7,4,9
+"
+            )
+            .is_err());
+    }
 }
@@ -1,15 +1,17 @@
-use advent_of_code_2021::read_file_lines;
+use crate::generate::InputRng;
+use crate::{try_parse_lines_as, Error, Solution};
 use array2d::Array2D;
+use rand::Rng;
 use sscanf::scanf;
 
-#[derive(Debug)]
-enum LineType {
+#[derive(Debug, PartialEq)]
+pub enum LineType {
     Straight,
     Diagonal,
 }
 
 #[derive(Debug)]
-struct Line {
+pub struct Line {
     from_x: u32,
     from_y: u32,
     to_x: u32,
@@ -18,8 +20,9 @@ struct Line {
 }
 
 impl Line {
-    fn scan_str(s: &str) -> (u32, u32, u32, u32) {
-        scanf!(s, "{},{} -> {},{}", u32, u32, u32, u32).unwrap()
+    fn scan_str(s: &str) -> Result<(u32, u32, u32, u32), String> {
+        scanf!(s, "{},{} -> {},{}", u32, u32, u32, u32)
+            .ok_or_else(|| format!("'{}' is not a line of the form 'x1,y1 -> x2,y2'", s))
     }
 
     fn construct(coords: (u32, u32, u32, u32), line_type: LineType) -> Self {
@@ -44,25 +47,25 @@ impl Line {
         from_x == to_x || from_y == to_y
     }
 
-    pub fn parse(s: &String) -> Line {
+    fn is_diagonal((from_x, from_y, to_x, to_y): (u32, u32, u32, u32)) -> bool {
+        from_x.abs_diff(to_x) == from_y.abs_diff(to_y)
+    }
+
+    pub fn parse(s: &str) -> Result<Line, String> {
         use LineType::*;
-        let coords = Self::scan_str(s);
+        let coords = Self::scan_str(s)?;
         let line_type = if Self::is_straight(coords) {
             Straight
-        } else {
+        } else if Self::is_diagonal(coords) {
             Diagonal
+        } else {
+            return Err(format!("'{}' is neither straight nor diagonal", s));
         };
-        Self::construct(coords, line_type)
+        Ok(Self::construct(coords, line_type))
     }
 
-    pub fn parse_straight(s: &String) -> Option<Line> {
-        let coords = Self::scan_str(s);
-
-        if Self::is_straight(coords) {
-            Some(Self::construct(coords, LineType::Straight))
-        } else {
-            None
-        }
+    pub fn is_straight_line(&self) -> bool {
+        self.line_type == LineType::Straight
     }
 
     pub fn get_highest_x_y(&self) -> (u32, u32) {
@@ -108,7 +111,7 @@ impl Line {
     }
 }
 
-fn get_map_size(lines: &Vec<Line>) -> (u32, u32) {
+fn get_map_size(lines: &[&Line]) -> (u32, u32) {
     let (highest_x, highest_y) = lines.iter().fold((0, 0), |(c_x, c_y), l| {
         let (l_x, l_y) = l.get_highest_x_y();
         (c_x.max(l_x), c_y.max(l_y))
@@ -117,7 +120,7 @@ fn get_map_size(lines: &Vec<Line>) -> (u32, u32) {
     (highest_x + 1, highest_y + 1)
 }
 
-struct Map {
+pub struct Map {
     tiles: Array2D<u32>,
 }
 
@@ -154,7 +157,7 @@ impl Map {
     }
 }
 
-fn calculate_dangerous_areas(lines: &Vec<Line>) -> u32 {
+fn calculate_dangerous_areas(lines: &[&Line]) -> u32 {
     let (width, height) = get_map_size(&lines);
 
     let mut map = Map::new(width as usize, height as usize);
@@ -166,33 +169,96 @@ fn calculate_dangerous_areas(lines: &Vec<Line>) -> u32 {
     map.sum_of_dangerous_areas()
 }
 
-fn calculate_dangerous_areas_only_straight(lines: &Vec<String>) {
+fn calculate_dangerous_areas_only_straight(lines: &[Line]) -> u32 {
     let lines = lines
         .iter()
-        .filter_map(Line::parse_straight)
+        .filter(|l| l.is_straight_line())
         .collect::<Vec<_>>();
 
-    let result = calculate_dangerous_areas(&lines);
+    calculate_dangerous_areas(&lines)
+}
 
-    println!(
-        "The sum of dangerous areas when only dealing with straight lines is: {}",
-        result
-    );
+/// Generates `size` horizontal, vertical and diagonal lines on a 1000x1000 floor.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let (x1, y1) = (rng.gen_range(0..1000), rng.gen_range(0..1000));
+            let (x2, y2) = match rng.gen_range(0..3) {
+                0 => (rng.gen_range(0..1000), y1),
+                1 => (x1, rng.gen_range(0..1000)),
+                _ => {
+                    // as far as the line can go in a random diagonal direction without leaving
+                    // the floor
+                    let (dx, dy) = (rng.gen_range(0..2) * 2 - 1, rng.gen_range(0..2) * 2 - 1);
+                    let room_x = if dx > 0 { 999 - x1 } else { x1 };
+                    let room_y = if dy > 0 { 999 - y1 } else { y1 };
+                    let length = rng.gen_range(0..=room_x.min(room_y));
+                    (x1 + dx * length, y1 + dy * length)
+                }
+            };
+            format!("{},{} -> {},{}\n", x1, y1, x2, y2)
+        })
+        .collect()
 }
 
-fn calculate_dangerous_areas_all(lines: &Vec<String>) {
-    let lines = lines.iter().map(Line::parse).collect::<Vec<_>>();
+pub struct Day5;
 
-    let result = calculate_dangerous_areas(&lines);
+impl Solution for Day5 {
+    type Input = Vec<Line>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        try_parse_lines_as(input, Line::parse)
+    }
 
-    println!(
-        "The sum of dangerous areas when dealing with all lines is: {}",
-        result
-    );
+    fn part1(&self, lines: &Self::Input) -> Self::Answer1 {
+        calculate_dangerous_areas_only_straight(lines)
+    }
+
+    fn part2(&self, lines: &Self::Input) -> Self::Answer2 {
+        calculate_dangerous_areas(&lines.iter().collect::<Vec<_>>())
+    }
 }
 
-fn main() {
-    let lines = read_file_lines("input/day5.txt");
-    calculate_dangerous_areas_only_straight(&lines);
-    calculate_dangerous_areas_all(&lines);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+0,9 -> 5,9
+8,0 -> 0,8
+9,4 -> 3,4
+2,2 -> 2,1
+7,0 -> 7,4
+6,4 -> 2,0
+0,9 -> 2,9
+3,4 -> 1,4
+0,0 -> 8,8
+5,5 -> 8,2
+";
+
+    #[test]
+    fn example_part1() {
+        let input = Day5.parse(EXAMPLE).unwrap();
+        assert_eq!(Day5.part1(&input), 5);
+    }
+
+    #[test]
+    fn example_part2() {
+        let input = Day5.parse(EXAMPLE).unwrap();
+        assert_eq!(Day5.part2(&input), 12);
+    }
+
+    #[test]
+    fn diagonal_line_positions() {
+        let line = Line::parse("9,7 -> 7,9").unwrap();
+        assert!(!line.is_straight_line());
+        assert_eq!(line.get_line_positions(), vec![(7, 9), (8, 8), (9, 7)]);
+    }
+
+    #[test]
+    fn rejects_lines_at_other_angles() {
+        assert!(Line::parse("0,0 -> 1,2").is_err());
+    }
 }
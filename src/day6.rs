@@ -1,18 +1,27 @@
-use advent_of_code_2021::read_file_to_string;
+use crate::generate::InputRng;
+use crate::{
+    debug, info, trace, try_parse_separated_as, Error, LineError, Param, Params, Solution,
+};
+use rand::Rng;
 use std::time::Instant;
 
 const NUM_OF_STATES: usize = 8;
 const TIMER_RESET_VALUE: usize = 6;
 
 #[derive(Clone)]
-struct LanternFish {
+pub struct LanternFish {
     timer: u8,
 }
 
 impl LanternFish {
-    pub fn parse(s: &str) -> Self {
-        let timer = s.parse().unwrap();
-        Self { timer }
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.parse() {
+            Ok(timer) if timer as usize <= NUM_OF_STATES => Ok(Self { timer }),
+            _ => Err(format!(
+                "'{}' is not a valid timer, expected 0-{}",
+                s, NUM_OF_STATES
+            )),
+        }
     }
 
     pub fn tick(&mut self) -> Option<LanternFish> {
@@ -32,8 +41,8 @@ impl LanternFish {
     }
 }
 
-fn parse_fish(raw: &str) -> Vec<LanternFish> {
-    raw.split(',').map(LanternFish::parse).collect()
+fn parse_fish(raw: &str) -> Result<Vec<LanternFish>, LineError> {
+    try_parse_separated_as(raw, ',', LanternFish::parse)
 }
 
 fn simulate_one_day_slow(fish: &mut Vec<LanternFish>) {
@@ -51,29 +60,27 @@ fn simulate_one_day_slow(fish: &mut Vec<LanternFish>) {
 fn simulate_slow(mut fish: Vec<LanternFish>, num_of_days: u32) -> u64 {
     let start = Instant::now();
     for day in 0..num_of_days {
-        println!("Day {}", day);
         simulate_one_day_slow(&mut fish);
-        println!("End of day {}, fish: {}", day, fish.len());
+        trace!("End of day {}, fish: {}", day, fish.len());
     }
     let duration = start.elapsed();
 
-    println!("Simulation took: {:?}", duration);
+    debug!("Slow simulation took {:?}", duration);
 
     fish.len() as u64
 }
 
 #[derive(Debug)]
-struct FishSchool {
+pub struct FishSchool {
     num_of_fish_by_timer: Vec<u64>,
 }
 
 impl FishSchool {
-    pub fn parse(s: &str) -> Self {
+    pub fn from_fish(fish: &[LanternFish]) -> Self {
         let mut num_of_fish_by_timer = vec![0; NUM_OF_STATES + 1];
 
-        s.split(',')
-            .map(|d| d.parse::<usize>().unwrap())
-            .for_each(|i| num_of_fish_by_timer[i] += 1);
+        fish.iter()
+            .for_each(|f| num_of_fish_by_timer[f.timer as usize] += 1);
 
         Self {
             num_of_fish_by_timer,
@@ -83,12 +90,11 @@ impl FishSchool {
     pub fn simulate(&mut self, num_of_days: u64) -> u64 {
         let start = Instant::now();
         for day in 0..num_of_days {
-            println!("Day {}", day);
             self.tick();
-            println!("End of day {}, fish: {}", day, self.num_of_all_fish());
+            trace!("End of day {}, fish: {}", day, self.num_of_all_fish());
         }
         let duration = start.elapsed();
-        println!("Simulation took: {:?}", duration);
+        debug!("Fast simulation took {:?}", duration);
         self.num_of_all_fish()
     }
 
@@ -105,38 +111,113 @@ impl FishSchool {
     }
 }
 
-fn slow_way(raw: &str) {
-    let fish = parse_fish(&raw);
-    let num_of_fish_after_80_days = simulate_slow(fish.clone(), 80);
-    println!(
-        "The number of lanternfish after 80 days is {}",
-        num_of_fish_after_80_days
-    );
-
-    /*let num_of_fish_after_256_days = simulate_slow(fish, 256);
-    println!(
-        "The number of lanternfish after 256 days is {}",
-        num_of_fish_after_256_days
-    );*/
+/// Generates `size` lanternfish with internal timers of 1-5, like the initial school.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    let timers = (0..size).map(|_| rng.gen_range(1..=5).to_string());
+    format!("{}\n", timers.collect::<Vec<_>>().join(","))
 }
 
-fn fast_way(raw: &str) {
-    let mut school1 = FishSchool::parse(&raw);
-    let num_of_fish_after_80_days = school1.simulate(80);
-    println!(
-        "The number of lanternfish after 80 days is {}",
-        num_of_fish_after_80_days
-    );
-    let mut school2 = FishSchool::parse(&raw);
-    let num_of_fish_after_256_days = school2.simulate(256);
-    println!(
-        "The number of lanternfish after 256 days is {}",
-        num_of_fish_after_256_days
-    );
+/// The slow simulation keeps every fish, and the school grows about tenfold every 27 days, so a few
+/// hundred fish take tens of megabytes by this many days and ever more after.
+const MAX_SLOW_DAYS: usize = 128;
+
+pub const PART1_DAYS: Param = Param {
+    name: "part1_days",
+    default: 80,
+    description: "the days to simulate in the first part, slowly as well, so keep them few",
+    check: |days| Param::in_range(days, 0..=MAX_SLOW_DAYS),
+};
+
+pub const PART2_DAYS: Param = Param {
+    name: "part2_days",
+    default: 256,
+    description: "the days to simulate in the second part",
+    check: Param::any_value,
+};
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    type Input = Vec<LanternFish>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    const PARAMS: &'static [Param] = &[PART1_DAYS, PART2_DAYS];
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        parse_fish(input.trim()).map_err(|e| Error::malformed(1, e))
+    }
+
+    fn part1(&self, fish: &Self::Input) -> Self::Answer1 {
+        self.part1_with(fish, &Params::default())
+    }
+
+    fn part2(&self, fish: &Self::Input) -> Self::Answer2 {
+        self.part2_with(fish, &Params::default())
+    }
+
+    fn part1_with(&self, fish: &Self::Input, params: &Params) -> Self::Answer1 {
+        let num_of_days = params.get(&PART1_DAYS);
+        let num_of_fish_slow = simulate_slow(fish.clone(), num_of_days as u32);
+        let num_of_fish_fast = FishSchool::from_fish(fish).simulate(num_of_days as u64);
+        info!(
+            "The slow way found {} fish, the fast way found {} fish",
+            num_of_fish_slow, num_of_fish_fast
+        );
+        num_of_fish_fast
+    }
+
+    fn part2_with(&self, fish: &Self::Input, params: &Params) -> Self::Answer2 {
+        FishSchool::from_fish(fish).simulate(params.get(&PART2_DAYS) as u64)
+    }
 }
 
-fn main() {
-    let raw = read_file_to_string("input/day6.txt");
-    slow_way(&raw);
-    fast_way(&raw);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "3,4,3,1,2\n";
+
+    #[test]
+    fn example_part1() {
+        let input = Day6.parse(EXAMPLE).unwrap();
+        assert_eq!(Day6.part1(&input), 5934);
+    }
+
+    #[test]
+    fn example_part2() {
+        let input = Day6.parse(EXAMPLE).unwrap();
+        assert_eq!(Day6.part2(&input), 26984457539);
+    }
+
+    #[test]
+    fn slow_and_fast_simulations_agree_after_18_days() {
+        let fish = Day6.parse(EXAMPLE).unwrap();
+        assert_eq!(simulate_slow(fish.clone(), 18), 26);
+        assert_eq!(FishSchool::from_fish(&fish).simulate(18), 26);
+    }
+
+    #[test]
+    fn part1_simulates_the_given_days() {
+        let fish = Day6.parse(EXAMPLE).unwrap();
+        let params = Params::parse(Day6::PARAMS, &["part1_days=18"]).unwrap();
+        assert_eq!(Day6.part1_with(&fish, &params), 26);
+        // too many to keep every fish in memory
+        assert!(Params::parse(Day6::PARAMS, &["part1_days=256"]).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn slow_and_fast_simulations_agree(
+            timers in prop::collection::vec(0..=NUM_OF_STATES as u8, 0..30),
+            num_of_days in 0..60u32,
+        ) {
+            let fish = timers.into_iter().map(|timer| LanternFish { timer }).collect::<Vec<_>>();
+            prop_assert_eq!(
+                simulate_slow(fish.clone(), num_of_days),
+                FishSchool::from_fish(&fish).simulate(num_of_days as u64)
+            );
+        }
+    }
 }
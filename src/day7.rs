@@ -1,4 +1,6 @@
-use advent_of_code_2021::read_file_to_string;
+use crate::generate::InputRng;
+use crate::{try_parse_separated_as, Error, Solution};
+use rand::Rng;
 
 fn sum_of_first_n_integers(n: u32) -> u32 {
     // (1..=n).sum()
@@ -25,40 +27,75 @@ fn solve_using_formula(
     })
 }
 
-fn solve_using_linear_consumption(positions: &Vec<u32>) {
-    let min_fuel = solve_using_formula(&positions, |pos, acc, next| {
+fn solve_using_linear_consumption(positions: &Vec<u32>) -> u32 {
+    solve_using_formula(positions, |pos, acc, next| {
         let crab_fuel = distance(pos, next);
         acc + crab_fuel
-    });
-
-    println!(
-        "The minimum required fuel assuming linear consumption is {}",
-        min_fuel
-    );
+    })
 }
 
-fn solve_using_increasing_consumption(positions: &Vec<u32>) {
-    let min_fuel = solve_using_formula(&positions, |pos, acc, next| {
+fn solve_using_increasing_consumption(positions: &Vec<u32>) -> u32 {
+    solve_using_formula(positions, |pos, acc, next| {
         let distance = distance(pos, next);
         let crab_fuel = sum_of_first_n_integers(distance);
         acc + crab_fuel
-    });
+    })
+}
 
-    println!(
-        "The minimum required fuel assuming increasing consumption is {}",
-        min_fuel
-    );
+/// Generates the horizontal positions of `size` crabs, most of them near the start.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    let positions = (0..size).map(|_| {
+        let spread = if rng.gen_bool(0.8) { 500 } else { 2000 };
+        rng.gen_range(0..spread).to_string()
+    });
+    format!("{}\n", positions.collect::<Vec<_>>().join(","))
 }
 
-fn parse_file(path: &str) -> Vec<u32> {
-    read_file_to_string(path)
-        .split(',')
-        .map(|d| d.parse::<u32>().unwrap())
-        .collect::<Vec<_>>()
+pub struct Day7;
+
+impl Solution for Day7 {
+    type Input = Vec<u32>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        try_parse_separated_as(input.trim(), ',', |d| {
+            d.parse::<u32>()
+                .map_err(|_| format!("'{}' is not a valid position", d))
+        })
+        .map_err(|e| Error::malformed(1, e))
+    }
+
+    fn part1(&self, positions: &Self::Input) -> Self::Answer1 {
+        solve_using_linear_consumption(positions)
+    }
+
+    fn part2(&self, positions: &Self::Input) -> Self::Answer2 {
+        solve_using_increasing_consumption(positions)
+    }
 }
 
-fn main() {
-    let positions = parse_file("input/day7.txt");
-    solve_using_linear_consumption(&positions);
-    solve_using_increasing_consumption(&positions);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "16,1,2,0,4,2,7,1,2,14\n";
+
+    #[test]
+    fn example_part1() {
+        let input = Day7.parse(EXAMPLE).unwrap();
+        assert_eq!(Day7.part1(&input), 37);
+    }
+
+    #[test]
+    fn example_part2() {
+        let input = Day7.parse(EXAMPLE).unwrap();
+        assert_eq!(Day7.part2(&input), 168);
+    }
+
+    #[test]
+    fn increasing_consumption_of_a_single_move() {
+        assert_eq!(sum_of_first_n_integers(distance(16, 5)), 66);
+        assert_eq!(sum_of_first_n_integers(distance(1, 5)), 10);
+    }
 }
@@ -1,6 +1,8 @@
-use advent_of_code_2021::{read_file_lines_as, vec_to_array};
+use crate::generate::InputRng;
+use crate::{try_parse_lines_as, vec_to_array, Error, Solution};
 use itertools::Itertools;
 use phf::phf_map;
+use rand::seq::SliceRandom;
 use std::collections::HashMap;
 
 // only `1` is 2-length, `4` is 4-length, `7` is 3-length and `8` is 7-length
@@ -12,18 +14,20 @@ const TRIVIAL_DIGITS: phf::Map<u32, u8> = phf_map! {
 };
 
 #[derive(Debug)]
-struct DisplayConfig {
+pub struct DisplayConfig {
     patterns: [String; 10],
     output: [String; 4],
 }
 
 impl DisplayConfig {
-    pub fn parse(s: &str) -> Self {
-        let (patterns_raw, output_raw) = s.split_once('|').unwrap();
-        Self {
-            patterns: split_then_sort_chars(patterns_raw),
-            output: split_then_sort_chars(output_raw),
-        }
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let (patterns_raw, output_raw) = s
+            .split_once('|')
+            .ok_or_else(|| format!("Could not split '{}' into patterns and output", s))?;
+        Ok(Self {
+            patterns: split_then_sort_chars(patterns_raw)?,
+            output: split_then_sort_chars(output_raw)?,
+        })
     }
 
     pub fn decode_all(&self) -> [u8; 4] {
@@ -134,12 +138,16 @@ fn str_contains_chars(s: &str, candidates: &str) -> bool {
     candidates.chars().all(|c| s.contains(c))
 }
 
-fn split_then_sort_chars<const N: usize>(s: &str) -> [String; N] {
-    split_whitespace_to_array(s).map(|segment| sort_chars_in_string(&segment))
+fn split_then_sort_chars<const N: usize>(s: &str) -> Result<[String; N], String> {
+    split_whitespace_to_array(s).map(|a| a.map(|segment| sort_chars_in_string(&segment)))
 }
 
-fn split_whitespace_to_array<const N: usize>(s: &str) -> [String; N] {
-    vec_to_array(s.split_whitespace().map(String::from).collect())
+fn split_whitespace_to_array<const N: usize>(s: &str) -> Result<[String; N], String> {
+    s.split_whitespace()
+        .map(String::from)
+        .collect::<Vec<_>>()
+        .try_into()
+        .map_err(|v: Vec<_>| format!("Expected {} patterns but found {}", N, v.len()))
 }
 
 fn sort_chars_in_string(s: &str) -> String {
@@ -150,19 +158,100 @@ fn map_then_sum_configs(configs: &Vec<DisplayConfig>, mapper: fn(&DisplayConfig)
     configs.iter().map(mapper).sum()
 }
 
-fn count_trivial_digits_in_configs(configs: &Vec<DisplayConfig>) {
-    let num_of_trivial_digits =
-        map_then_sum_configs(&configs, DisplayConfig::count_trivial_digits_in_output);
-    println!("The count of trivial digits is {}", num_of_trivial_digits);
+/// Generates `size` displays, each with its own randomly crossed wires.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    const DIGITS: [&str; 10] = [
+        "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg",
+    ];
+
+    /// Lights up the segments of `digit` through the crossed `wires`, in a random order.
+    fn scramble(rng: &mut InputRng, wires: &[char], digit: &str) -> String {
+        let mut segments = digit
+            .chars()
+            .map(|c| wires[(c as u8 - b'a') as usize])
+            .collect::<Vec<_>>();
+        segments.shuffle(rng);
+        segments.into_iter().collect()
+    }
+
+    (0..size)
+        .map(|_| {
+            let mut wires = "abcdefg".chars().collect::<Vec<_>>();
+            wires.shuffle(rng);
+
+            let mut patterns = DIGITS
+                .iter()
+                .map(|d| scramble(rng, &wires, d))
+                .collect::<Vec<_>>();
+            let output = (0..4)
+                .map(|_| {
+                    let digit = DIGITS.choose(rng).unwrap();
+                    scramble(rng, &wires, digit)
+                })
+                .collect::<Vec<_>>();
+            patterns.shuffle(rng);
+
+            format!("{} | {}\n", patterns.join(" "), output.join(" "))
+        })
+        .collect()
 }
 
-fn calculate_sum_of_output_values_in_configs(configs: &Vec<DisplayConfig>) {
-    let sum_of_outputs = map_then_sum_configs(&configs, DisplayConfig::calculate_decoded_output);
-    println!("The sum of all outputs is {}", sum_of_outputs);
+pub struct Day8;
+
+impl Solution for Day8 {
+    type Input = Vec<DisplayConfig>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        try_parse_lines_as(input, DisplayConfig::parse)
+    }
+
+    fn part1(&self, configs: &Self::Input) -> Self::Answer1 {
+        map_then_sum_configs(configs, DisplayConfig::count_trivial_digits_in_output)
+    }
+
+    fn part2(&self, configs: &Self::Input) -> Self::Answer2 {
+        map_then_sum_configs(configs, DisplayConfig::calculate_decoded_output)
+    }
 }
 
-fn main() {
-    let configs = read_file_lines_as("input/day8.txt", DisplayConfig::parse);
-    count_trivial_digits_in_configs(&configs);
-    calculate_sum_of_output_values_in_configs(&configs);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
+edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
+fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
+fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb
+aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea
+fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
+dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe
+bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef
+egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
+gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce
+";
+
+    #[test]
+    fn example_part1() {
+        let input = Day8.parse(EXAMPLE).unwrap();
+        assert_eq!(Day8.part1(&input), 26);
+    }
+
+    #[test]
+    fn example_part2() {
+        let input = Day8.parse(EXAMPLE).unwrap();
+        assert_eq!(Day8.part2(&input), 61229);
+    }
+
+    #[test]
+    fn decode_single_entry() {
+        let config = DisplayConfig::parse(
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf",
+        )
+        .unwrap();
+        assert_eq!(config.decode_all(), [5, 3, 5, 3]);
+        assert_eq!(config.calculate_decoded_output(), 5353);
+    }
 }
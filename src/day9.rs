@@ -1,33 +1,26 @@
-use advent_of_code_2021::{
-    parse_2d_number_grid, print_2d_array, read_file_to_string, vec_to_array,
-};
-use array2d::Array2D;
+use crate::generate::{generate_digit_grid, InputRng};
+use crate::{debug, vec_to_array, Coords, Error, Grid, Solution};
 use itertools::Itertools;
 use std::collections::HashSet;
 
 #[derive(Debug)]
-struct HeightMap {
-    tiles: Array2D<u8>,
+pub struct HeightMap {
+    tiles: Grid<u8>,
 }
 
 impl HeightMap {
-    pub fn parse(s: &str) -> Self {
-        Self {
-            tiles: parse_2d_number_grid(s),
-        }
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        Ok(Self {
+            tiles: Grid::parse_digits(s)?,
+        })
     }
 
     pub fn find_local_minima_values(&self) -> Vec<(usize, usize, u8)> {
-        let mut res = Vec::new();
-        for (y, row) in self.tiles.rows_iter().enumerate() {
-            for (x, column) in row.enumerate() {
-                if self.is_local_minimum(x, y) {
-                    res.push((x, y, *column));
-                    // println!("Found local minimum: ({}, {}) -> {}", y, x, column);
-                }
-            }
-        }
-        res
+        self.tiles
+            .iter()
+            .filter(|&(coords, _)| self.is_local_minimum(coords))
+            .map(|((x, y), &height)| (x, y, height))
+            .collect()
     }
 
     pub fn calculate_risk_value(&self) -> u32 {
@@ -37,11 +30,8 @@ impl HeightMap {
             .sum()
     }
 
-    pub fn print_map(&self) {
-        print_2d_array(&self.tiles);
-    }
-
-    pub fn print_basin_map(&self) {
+    /// Draws the map with every tile in a basin as `@` and every other one as `_`.
+    pub fn basin_map(&self) -> String {
         let local_minima = self.find_local_minima_values();
 
         let tiles_in_a_basin = local_minima
@@ -50,16 +40,18 @@ impl HeightMap {
             .flatten()
             .collect::<HashSet<_>>();
 
-        for (y, row) in self.tiles.rows_iter().enumerate() {
-            for (x, _) in row.enumerate() {
-                if tiles_in_a_basin.contains(&(x, y)) {
-                    print!("@");
-                } else {
-                    print!("_");
-                }
+        let mut map = String::new();
+        for (x, y) in self.tiles.coords() {
+            map.push(if tiles_in_a_basin.contains(&(x, y)) {
+                '@'
+            } else {
+                '_'
+            });
+            if x + 1 == self.tiles.width() {
+                map.push('\n');
             }
-            println!();
         }
+        map
     }
 
     pub fn get_basin_sizes(&self) -> Vec<u32> {
@@ -88,28 +80,12 @@ impl HeightMap {
             .fold(1, |acc, next| acc * next)
     }
 
-    fn is_local_minimum(&self, x: usize, y: usize) -> bool {
-        let height = self.tiles.get(y, x).unwrap();
-
-        let mut neighbours = Vec::with_capacity(4);
-
-        if y != 0 {
-            neighbours.push(*self.tiles.get(y - 1, x).unwrap());
-        }
-
-        if x != 0 {
-            neighbours.push(*self.tiles.get(y, x - 1).unwrap());
-        }
-
-        if (y + 1) != self.tiles.num_rows() {
-            neighbours.push(*self.tiles.get(y + 1, x).unwrap());
-        }
-
-        if (x + 1) != self.tiles.num_columns() {
-            neighbours.push(*self.tiles.get(y, x + 1).unwrap());
-        }
+    fn is_local_minimum(&self, coords: Coords) -> bool {
+        let height = self.tiles[coords];
 
-        neighbours.iter().all(|neighbour| height < neighbour)
+        self.tiles
+            .neighbours4(coords)
+            .all(|neighbour| height < self.tiles[neighbour])
     }
 
     fn get_basin_size(&self, x: usize, y: usize) -> u32 {
@@ -118,51 +94,85 @@ impl HeightMap {
 
     fn get_basin(&self, x: usize, y: usize) -> HashSet<(usize, usize)> {
         let mut positions = HashSet::new();
-        self.get_basin_rec(x as i32, y as i32, &mut positions);
+        self.get_basin_rec((x, y), &mut positions);
         positions
     }
 
-    fn get_basin_rec(&self, x: i32, y: i32, acc: &mut HashSet<(usize, usize)>) {
-        if x < 0
-            || x >= self.tiles.num_columns() as i32
-            || y < 0
-            || y >= self.tiles.num_rows() as i32
-        {
+    fn get_basin_rec(&self, coords: Coords, acc: &mut HashSet<Coords>) {
+        if self.is_top(coords) {
             return;
         }
 
-        if self.is_top(x, y) {
+        if !acc.insert(coords) {
             return;
         }
 
-        if acc.contains(&(x as usize, y as usize)) {
-            return;
+        for neighbour in self.tiles.neighbours4(coords) {
+            self.get_basin_rec(neighbour, acc);
         }
-
-        acc.insert((x as usize, y as usize));
-
-        self.get_basin_rec(x - 1, y, acc);
-        self.get_basin_rec(x, y - 1, acc);
-        self.get_basin_rec(x + 1, y, acc);
-        self.get_basin_rec(x, y + 1, acc);
     }
 
-    fn is_top(&self, x: i32, y: i32) -> bool {
-        *self.tiles.get(y as usize, x as usize).unwrap() == 9
+    fn is_top(&self, coords: Coords) -> bool {
+        self.tiles[coords] == 9
     }
 }
 
-fn main() {
-    let input = read_file_to_string("input/day9.txt");
-    let height_map = HeightMap::parse(&input);
+/// Generates a `size`x`size` heightmap.
+pub fn generate(rng: &mut InputRng, size: usize) -> String {
+    generate_digit_grid(rng, size, 0..=9)
+}
+
+pub struct Day9;
 
-    height_map.print_map();
+impl Solution for Day9 {
+    type Input = HeightMap;
+    type Answer1 = u32;
+    type Answer2 = u32;
 
-    let risk_value = height_map.calculate_risk_value();
-    println!("The risk value is {}", risk_value);
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        HeightMap::parse(input)
+    }
 
-    height_map.print_basin_map();
+    fn part1(&self, height_map: &Self::Input) -> Self::Answer1 {
+        debug!("Height map:\n{}", height_map.tiles);
+        height_map.calculate_risk_value()
+    }
 
-    let result = height_map.calculate_product_of_three_largest_basin_sizes();
-    println!("The product of the 3 largest basin sizes is {}", result);
+    fn part2(&self, height_map: &Self::Input) -> Self::Answer2 {
+        debug!("Basins:\n{}", height_map.basin_map());
+        height_map.calculate_product_of_three_largest_basin_sizes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+2199943210
+3987894921
+9856789892
+8767896789
+9899965678
+";
+
+    #[test]
+    fn example_part1() {
+        let input = Day9.parse(EXAMPLE).unwrap();
+        assert_eq!(Day9.part1(&input), 15);
+    }
+
+    #[test]
+    fn example_part2() {
+        let input = Day9.parse(EXAMPLE).unwrap();
+        assert_eq!(Day9.part2(&input), 1134);
+    }
+
+    #[test]
+    fn basin_sizes_of_example() {
+        let height_map = HeightMap::parse(EXAMPLE).unwrap();
+        let mut sizes = height_map.get_basin_sizes();
+        sizes.sort_unstable();
+        assert_eq!(sizes, vec![3, 9, 9, 14]);
+    }
 }
@@ -0,0 +1,240 @@
+use crate::{
+    day1, day10, day11, day12, day13, day14, day15, day16, day17, day18, day19, day2, day20, day21,
+    day22, day23, day24, day25, day3, day4, day5, day6, day7, day8, day9,
+};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::ops::RangeInclusive;
+
+/// The random number generator inputs are generated with. Unlike `rand::rngs::StdRng`, it yields
+/// the same numbers for a seed on every platform and in every version, so a seed is enough to
+/// reproduce an input.
+pub type InputRng = ChaCha8Rng;
+
+pub const DEFAULT_SEED: u64 = 0;
+
+/// Generates random, valid inputs of a day.
+pub struct Generator {
+    /// What the size of the input counts.
+    pub size: &'static str,
+    /// A size close to that of the real puzzle input.
+    pub default_size: usize,
+    pub generate: fn(&mut InputRng, usize) -> String,
+}
+
+/// The input generator of every day, indexed by the day minus one.
+pub const GENERATORS: [Generator; 25] = [
+    Generator {
+        size: "depth measurements",
+        default_size: 2000,
+        generate: day1::generate,
+    },
+    Generator {
+        size: "commands",
+        default_size: 1000,
+        generate: day2::generate,
+    },
+    Generator {
+        size: "diagnostic reports",
+        default_size: 1000,
+        generate: day3::generate,
+    },
+    Generator {
+        size: "bingo boards",
+        default_size: 100,
+        generate: day4::generate,
+    },
+    Generator {
+        size: "vent lines",
+        default_size: 500,
+        generate: day5::generate,
+    },
+    Generator {
+        size: "lanternfish",
+        default_size: 300,
+        generate: day6::generate,
+    },
+    Generator {
+        size: "crabs",
+        default_size: 1000,
+        generate: day7::generate,
+    },
+    Generator {
+        size: "displays",
+        default_size: 200,
+        generate: day8::generate,
+    },
+    Generator {
+        size: "width and height of the heightmap",
+        default_size: 100,
+        generate: day9::generate,
+    },
+    Generator {
+        size: "lines",
+        default_size: 110,
+        generate: day10::generate,
+    },
+    Generator {
+        size: "width and height of the octopus grid",
+        default_size: 10,
+        generate: day11::generate,
+    },
+    Generator {
+        size: "small caves",
+        default_size: 8,
+        generate: day12::generate,
+    },
+    Generator {
+        size: "dots",
+        default_size: 900,
+        generate: day13::generate,
+    },
+    Generator {
+        size: "elements of the polymer template",
+        default_size: 20,
+        generate: day14::generate,
+    },
+    Generator {
+        size: "width and height of the cave",
+        default_size: 100,
+        generate: day15::generate,
+    },
+    Generator {
+        size: "packets",
+        default_size: 250,
+        generate: day16::generate,
+    },
+    Generator {
+        size: "width of the target area",
+        default_size: 20,
+        generate: day17::generate,
+    },
+    Generator {
+        size: "snailfish numbers",
+        default_size: 100,
+        generate: day18::generate,
+    },
+    Generator {
+        size: "scanners",
+        default_size: 30,
+        generate: day19::generate,
+    },
+    Generator {
+        size: "width and height of the image",
+        default_size: 100,
+        generate: day20::generate,
+    },
+    Generator {
+        size: "nothing, the starting positions are all there is",
+        default_size: 0,
+        generate: day21::generate,
+    },
+    Generator {
+        size: "reboot steps",
+        default_size: 420,
+        generate: day22::generate,
+    },
+    Generator {
+        size: "depth of the side rooms",
+        default_size: 2,
+        generate: day23::generate,
+    },
+    Generator {
+        size: "pairs of digits checked against each other",
+        default_size: 7,
+        generate: day24::generate,
+    },
+    Generator {
+        size: "width and height of the ocean floor",
+        default_size: 140,
+        generate: day25::generate,
+    },
+];
+
+/// Generates an input for `day`, of the day's default size unless `size` is given. The same seed
+/// always yields the same input.
+pub fn generate_input(day: usize, size: Option<usize>, seed: u64) -> String {
+    let generator = &GENERATORS[day - 1];
+    let mut rng = InputRng::seed_from_u64(seed);
+    (generator.generate)(&mut rng, size.unwrap_or(generator.default_size))
+}
+
+/// Generates a `size`x`size` grid of digits in `digits`, the format of every day with a map.
+pub fn generate_digit_grid(rng: &mut InputRng, size: usize, digits: RangeInclusive<u8>) -> String {
+    (0..size)
+        .map(|_| {
+            let row = (0..size).map(|_| (b'0' + rng.gen_range(digits.clone())) as char);
+            row.collect::<String>() + "\n"
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Part, DAYS};
+
+    #[test]
+    fn every_generated_input_parses() {
+        for day in 1..=25 {
+            // day 19 places its scanners while parsing, which takes long at the default size
+            let size = if day == 19 { Some(10) } else { None };
+            for seed in 0..3 {
+                let input = generate_input(day, size, seed);
+                if let Err(e) = DAYS[day - 1].parse(&input) {
+                    panic!("day {} with seed {}: {}", day, seed, e);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn generated_inputs_are_solvable() {
+        // small inputs of the days whose answers depend on the input being well-formed beyond its
+        // syntax, like every bingo board winning eventually
+        let days = [
+            (3, 50),
+            (4, 5),
+            (8, 10),
+            (10, 10),
+            (12, 4),
+            (13, 50),
+            (16, 30),
+            (18, 5),
+            (19, 3),
+            (23, 2),
+        ];
+        for (day, size) in days {
+            let input = generate_input(day, Some(size), 1);
+            let parsed = DAYS[day - 1].parse(&input).unwrap();
+            parsed.solve(Part::One);
+            parsed.solve(Part::Two);
+        }
+    }
+
+    #[test]
+    fn extreme_sizes_still_parse() {
+        for (day, size) in [(12, 1000), (23, 0), (23, 1)] {
+            let input = generate_input(day, Some(size), 0);
+            if let Err(e) = DAYS[day - 1].parse(&input) {
+                panic!("day {} of size {}: {}", day, size, e);
+            }
+        }
+        let caves = generate_input(12, Some(1000), 0);
+        assert!(caves
+            .chars()
+            .all(|c| c.is_ascii_alphabetic() || c == '-' || c == '\n'));
+    }
+
+    #[test]
+    fn generation_is_reproducible() {
+        assert_eq!(
+            generate_input(5, Some(20), 7),
+            generate_input(5, Some(20), 7)
+        );
+        assert_ne!(
+            generate_input(5, Some(20), 7),
+            generate_input(5, Some(20), 8)
+        );
+    }
+}
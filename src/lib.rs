@@ -1,8 +1,505 @@
+pub mod answers;
+pub mod bench;
+pub mod cache;
+pub mod day1;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day2;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod generate;
+pub mod render;
+pub mod search;
+pub mod submissions;
+pub mod website;
+
 use array2d::Array2D;
-use std::{convert::TryInto, fmt::Display, fs::read_to_string, path::Path};
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    env,
+    fmt::Display,
+    fs::read_to_string,
+    io::{self, Read},
+    ops::{Index, IndexMut, RangeInclusive},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+/// Environment variable that points to the directory holding the `dayN.txt` inputs.
+pub const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+
+const DEFAULT_INPUT_DIR: &str = "input";
+
+/// Path that makes the `read_file_*` helpers read from stdin instead of a file.
+pub const STDIN_PATH: &str = "-";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub fn number(self) -> u8 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.number())
+    }
+}
+
+/// How much the days tell about what they are doing, from nothing at all to every step.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Quiet,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "quiet" => Ok(LogLevel::Quiet),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            "trace" => Ok(LogLevel::Trace),
+            _ => Err(format!(
+                "'{}' is not a log level, expected quiet, info, debug or trace",
+                s
+            )),
+        }
+    }
+}
+
+static LOG_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Info as u8);
+
+pub fn set_log_level(level: LogLevel) {
+    LOG_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Whether messages of `level` are currently logged.
+pub fn log_enabled(level: LogLevel) -> bool {
+    level != LogLevel::Quiet && level as u8 <= LOG_LEVEL.load(Ordering::Relaxed)
+}
+
+/// Logs a message to stderr, keeping stdout for the answers. The arguments are only evaluated if
+/// `level` is enabled, so expensive descriptions cost nothing otherwise.
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($arg:tt)*) => {
+        if $crate::log_enabled($level) {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+/// Logs a result worth knowing about, shown unless running quietly.
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => { $crate::log!($crate::LogLevel::Info, $($arg)*) };
+}
+
+/// Logs the intermediate states of a solution.
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)*) => { $crate::log!($crate::LogLevel::Debug, $($arg)*) };
+}
+
+/// Logs every single step of a solution.
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)*) => { $crate::log!($crate::LogLevel::Trace, $($arg)*) };
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    EmptyInput,
+    /// A line that could not be parsed; both `line` and `column` start from `1`.
+    Malformed {
+        line: usize,
+        column: Option<usize>,
+        reason: String,
+    },
+    /// Every line is well-formed, but the input as a whole does not describe a valid puzzle.
+    Invalid(String),
+}
+
+impl Error {
+    pub fn malformed(line: usize, e: impl Into<LineError>) -> Self {
+        let LineError { column, reason } = e.into();
+        Self::Malformed {
+            line,
+            column,
+            reason,
+        }
+    }
+
+    /// Moves the reported line further down, used when parsing a block that does not start at
+    /// the first line of the input.
+    pub fn shift_lines(self, offset: usize) -> Self {
+        match self {
+            Self::Malformed {
+                line,
+                column,
+                reason,
+            } => Self::Malformed {
+                line: line + offset,
+                column,
+                reason,
+            },
+            e => e,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io { path, source } if path == Path::new(STDIN_PATH) => {
+                write!(f, "could not read stdin: {}", source)
+            }
+            Self::Io { path, source } => {
+                write!(f, "could not read '{}': {}", path.display(), source)
+            }
+            Self::EmptyInput => write!(f, "the input is empty"),
+            Self::Malformed {
+                line,
+                column: Some(column),
+                reason,
+            } => write!(f, "line {}, column {}: {}", line, column, reason),
+            Self::Malformed {
+                line,
+                column: None,
+                reason,
+            } => write!(f, "line {}: {}", line, reason),
+            Self::Invalid(reason) => write!(f, "invalid input: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Reason why a single line could not be parsed, turned into an [`Error`] once the line is known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineError {
+    pub column: Option<usize>,
+    pub reason: String,
+}
+
+impl LineError {
+    pub fn at(column: usize, reason: impl Into<String>) -> Self {
+        Self {
+            column: Some(column),
+            reason: reason.into(),
+        }
+    }
+
+    /// Moves the reported column to the right, used when parsing a part in the middle of a line.
+    pub fn shift_columns(self, offset: usize) -> Self {
+        Self {
+            column: self.column.map(|c| c + offset),
+            ..self
+        }
+    }
+}
+
+impl From<String> for LineError {
+    fn from(reason: String) -> Self {
+        Self {
+            column: None,
+            reason,
+        }
+    }
+}
+
+impl From<&str> for LineError {
+    fn from(reason: &str) -> Self {
+        Self::from(reason.to_string())
+    }
+}
+
+/// A named knob of a puzzle, like the number of steps to simulate.
+#[derive(Debug, Copy, Clone)]
+pub struct Param {
+    pub name: &'static str,
+    /// The value the puzzle itself uses.
+    pub default: usize,
+    pub description: &'static str,
+    /// Checks a value given to the param, telling what was expected if it is not valid.
+    pub check: fn(usize) -> Result<(), String>,
+}
+
+impl Param {
+    /// A `check` for params that take any value.
+    pub fn any_value(_: usize) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Checks that `value` is within `range`, for the `check` of a param.
+    pub fn in_range(value: usize, range: RangeInclusive<usize>) -> Result<(), String> {
+        match (range.start(), range.end()) {
+            _ if range.contains(&value) => Ok(()),
+            (min, &usize::MAX) => Err(format!("expected at least {}", min)),
+            (min, max) => Err(format!("expected {} to {}", min, max)),
+        }
+    }
+}
+
+/// The values given to the params of a day; every other param keeps its default.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Params {
+    values: HashMap<String, usize>,
+}
+
+impl Params {
+    /// Parses `name=value` assignments to the `available` params of a day.
+    pub fn parse<S: AsRef<str>>(available: &[Param], assignments: &[S]) -> Result<Self, String> {
+        let mut values = HashMap::new();
+        for assignment in assignments {
+            let assignment = assignment.as_ref();
+            let (name, value) = assignment
+                .split_once('=')
+                .ok_or_else(|| format!("'{}' is not of the form 'name=value'", assignment))?;
+            let param = match available.iter().find(|param| param.name == name) {
+                Some(param) => param,
+                None if available.is_empty() => {
+                    return Err(format!("Unknown param '{}', the day has none", name))
+                }
+                None => {
+                    return Err(format!(
+                        "Unknown param '{}', expected one of {}",
+                        name,
+                        available
+                            .iter()
+                            .map(|param| param.name)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ))
+                }
+            };
+            let value = value
+                .parse()
+                .map_err(|_| format!("'{}' is not a valid value for '{}'", value, name))
+                .and_then(|value| {
+                    (param.check)(value).map(|_| value).map_err(|expected| {
+                        format!(
+                            "'{}' is not a valid value for '{}', {}",
+                            value, name, expected
+                        )
+                    })
+                })?;
+            values.insert(name.to_string(), value);
+        }
+
+        Ok(Self { values })
+    }
+
+    pub fn get(&self, param: &Param) -> usize {
+        self.values
+            .get(param.name)
+            .copied()
+            .unwrap_or(param.default)
+    }
+}
+
+/// Formats the given values as `name=value` pairs sorted by name, so equal params always look the
+/// same.
+impl Display for Params {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut values = self.values.iter().collect::<Vec<_>>();
+        values.sort();
+        let values = values
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>();
+        write!(f, "{}", values.join(","))
+    }
+}
+
+/// The solution of a single day: parses the raw puzzle input once, then solves both parts on it.
+pub trait Solution {
+    type Input;
+    type Answer1: Display;
+    type Answer2: Display;
+
+    /// The last day only has a single puzzle, in which case `part2` is never called.
+    const HAS_PART2: bool = true;
+
+    /// The knobs `part1_with` and `part2_with` can be turned with.
+    const PARAMS: &'static [Param] = &[];
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error>;
+
+    /// Parses the input to solve with the given params; only days with `PARAMS` the input has to
+    /// be checked against have to override it.
+    fn parse_with(&self, input: &str, _params: &Params) -> Result<Self::Input, Error> {
+        self.parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Self::Answer1;
+
+    fn part2(&self, input: &Self::Input) -> Self::Answer2;
+
+    /// Solves the first part with the given params; only days with `PARAMS` have to override it.
+    fn part1_with(&self, input: &Self::Input, _params: &Params) -> Self::Answer1 {
+        self.part1(input)
+    }
+
+    fn part2_with(&self, input: &Self::Input, _params: &Params) -> Self::Answer2 {
+        self.part2(input)
+    }
+}
+
+/// Type-erased counterpart of [`Solution`], so days with different input and answer types can be
+/// stored side by side.
+pub trait DynSolution {
+    fn has_part2(&self) -> bool;
+
+    fn params(&self) -> &'static [Param];
+
+    fn parse<'a>(&'a self, input: &str) -> Result<Box<dyn ParsedInput + 'a>, Error> {
+        self.parse_with(input, Params::default())
+    }
+
+    /// Parses `input`, which is then solved with `params`.
+    fn parse_with<'a>(
+        &'a self,
+        input: &str,
+        params: Params,
+    ) -> Result<Box<dyn ParsedInput + 'a>, Error>;
+}
+
+/// An already parsed input that can be solved for either part, formatting the answer as a `String`.
+pub trait ParsedInput {
+    fn solve(&self, part: Part) -> String;
+}
+
+struct Parsed<'a, S: Solution> {
+    solution: &'a S,
+    input: S::Input,
+    params: Params,
+}
+
+impl<'a, S: Solution> ParsedInput for Parsed<'a, S> {
+    fn solve(&self, part: Part) -> String {
+        match part {
+            Part::One => self
+                .solution
+                .part1_with(&self.input, &self.params)
+                .to_string(),
+            Part::Two => self
+                .solution
+                .part2_with(&self.input, &self.params)
+                .to_string(),
+        }
+    }
+}
+
+impl<S: Solution> DynSolution for S {
+    fn has_part2(&self) -> bool {
+        S::HAS_PART2
+    }
+
+    fn params(&self) -> &'static [Param] {
+        S::PARAMS
+    }
+
+    fn parse_with<'a>(
+        &'a self,
+        input: &str,
+        params: Params,
+    ) -> Result<Box<dyn ParsedInput + 'a>, Error> {
+        Ok(Box::new(Parsed {
+            solution: self,
+            input: Solution::parse_with(self, input, &params)?,
+            params,
+        }))
+    }
+}
+
+/// The solution of every day, indexed by the day minus one.
+pub const DAYS: [&dyn DynSolution; 25] = [
+    &day1::Day1,
+    &day2::Day2,
+    &day3::Day3,
+    &day4::Day4,
+    &day5::Day5,
+    &day6::Day6,
+    &day7::Day7,
+    &day8::Day8,
+    &day9::Day9,
+    &day10::Day10,
+    &day11::Day11,
+    &day12::Day12,
+    &day13::Day13,
+    &day14::Day14,
+    &day15::Day15,
+    &day16::Day16,
+    &day17::Day17,
+    &day18::Day18,
+    &day19::Day19,
+    &day20::Day20,
+    &day21::Day21,
+    &day22::Day22,
+    &day23::Day23,
+    &day24::Day24,
+    &day25::Day25,
+];
+
+/// Resolves where the input of `day` is read from: an explicitly given path (or `-` for stdin)
+/// wins, otherwise `dayN.txt` is looked up in `$AOC_INPUT_DIR`, falling back to `input/`.
+pub fn resolve_input_path(day: usize, path: Option<&str>) -> PathBuf {
+    match path {
+        Some(path) => PathBuf::from(path),
+        None => env::var_os(INPUT_DIR_VAR)
+            .map_or_else(|| PathBuf::from(DEFAULT_INPUT_DIR), PathBuf::from)
+            .join(format!("day{}.txt", day)),
+    }
+}
 
 pub fn read_file_to_string(path: impl AsRef<Path>) -> String {
-    read_to_string(path).unwrap()
+    try_read_file_to_string(path).unwrap_or_else(|e| panic!("{}", e))
 }
 
 pub fn read_file_lines(path: impl AsRef<Path>) -> Vec<String> {
@@ -11,17 +508,146 @@ pub fn read_file_lines(path: impl AsRef<Path>) -> Vec<String> {
 
 pub fn read_file_lines_as<T>(path: impl AsRef<Path>, f: fn(&str) -> T) -> Vec<T> {
     let contents = read_file_to_string(path);
-    contents.lines().map(f).collect()
+    parse_lines_as(&contents, f)
 }
 
 pub fn read_file_lines_filter_as<T>(path: impl AsRef<Path>, f: fn(&str) -> Option<T>) -> Vec<T> {
     let contents = read_file_to_string(path);
-    contents.lines().filter_map(f).collect()
+    normalized_lines(&contents).filter_map(f).collect()
 }
 
 pub fn read_file_lines_extract_first(path: impl AsRef<Path>) -> (String, Vec<String>) {
-    let mut input = read_file_lines(path)
-        .iter()
+    let contents = read_file_to_string(path);
+    parse_lines_extract_first(&contents)
+}
+
+pub fn try_read_file_to_string(path: impl AsRef<Path>) -> Result<String, Error> {
+    let path = path.as_ref();
+    let contents = if path == Path::new(STDIN_PATH) {
+        let mut contents = String::new();
+        io::stdin().read_to_string(&mut contents).map(|_| contents)
+    } else {
+        read_to_string(path)
+    }
+    .map_err(|source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    if contents.trim().is_empty() {
+        Err(Error::EmptyInput)
+    } else {
+        Ok(contents)
+    }
+}
+
+pub fn try_read_file_lines_as<T, E: Into<LineError>>(
+    path: impl AsRef<Path>,
+    f: impl Fn(&str) -> Result<T, E>,
+) -> Result<Vec<T>, Error> {
+    let contents = try_read_file_to_string(path)?;
+    try_parse_lines_as(&contents, f)
+}
+
+pub fn try_read_file_lines_extract_first(
+    path: impl AsRef<Path>,
+) -> Result<(String, Vec<String>), Error> {
+    let contents = try_read_file_to_string(path)?;
+    try_parse_lines_extract_first(&contents)
+}
+
+/// Iterates over the lines of `s` regardless of whether they end in `\n`, `\r\n` or a mix of the
+/// two, with trailing whitespace removed.
+pub fn normalized_lines(s: &str) -> impl Iterator<Item = &str> {
+    s.lines().map(str::trim_end)
+}
+
+/// A group of consecutive non-blank lines, like a single bingo board or scanner report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block<'a> {
+    /// Line number of the first line of the block in the whole input, starting from `1`.
+    pub first_line: usize,
+    pub lines: Vec<&'a str>,
+}
+
+impl<'a> Block<'a> {
+    /// Iterates over the lines of the block, each paired with its line number in the whole input.
+    pub fn numbered_lines(&self) -> impl Iterator<Item = (usize, &'a str)> + '_ {
+        self.lines
+            .iter()
+            .enumerate()
+            .map(move |(i, &line)| (self.first_line + i, line))
+    }
+
+    /// Joins the lines of the block with `\n`.
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// Parses the text of the block with a parser that reports line numbers relative to the start
+    /// of the block, turning them into line numbers of the whole input.
+    pub fn parse_with<T>(&self, f: impl FnOnce(&str) -> Result<T, Error>) -> Result<T, Error> {
+        f(&self.text()).map_err(|e| e.shift_lines(self.first_line - 1))
+    }
+
+    /// Returns the only line of the block, for blocks like the drawn numbers of day 4.
+    pub fn single_line(&self) -> Result<&'a str, Error> {
+        match self.lines[..] {
+            [line] => Ok(line),
+            _ => Err(Error::malformed(
+                self.first_line + 1,
+                "expected a blank line after the first line",
+            )),
+        }
+    }
+}
+
+/// Splits the input into blocks separated by one or more blank lines.
+pub fn split_blocks(s: &str) -> Vec<Block<'_>> {
+    let mut blocks = Vec::new();
+    let mut current: Option<Block> = None;
+    for (i, line) in normalized_lines(s).enumerate() {
+        if line.is_empty() {
+            blocks.extend(current.take());
+        } else {
+            current
+                .get_or_insert_with(|| Block {
+                    first_line: i + 1,
+                    lines: Vec::new(),
+                })
+                .lines
+                .push(line);
+        }
+    }
+    blocks.extend(current);
+
+    blocks
+}
+
+pub fn parse_lines(s: &str) -> Vec<String> {
+    parse_lines_as(s, str::to_string)
+}
+
+pub fn parse_lines_as<T>(s: &str, f: fn(&str) -> T) -> Vec<T> {
+    normalized_lines(s).map(f).collect()
+}
+
+pub fn parse_lines_extract_first(s: &str) -> (String, Vec<String>) {
+    try_parse_lines_extract_first(s).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_parse_lines_as<T, E: Into<LineError>>(
+    s: &str,
+    f: impl Fn(&str) -> Result<T, E>,
+) -> Result<Vec<T>, Error> {
+    normalized_lines(s)
+        .enumerate()
+        .map(|(i, line)| f(line).map_err(|e| Error::malformed(i + 1, e)))
+        .collect()
+}
+
+pub fn try_parse_lines_extract_first(s: &str) -> Result<(String, Vec<String>), Error> {
+    let mut input = normalized_lines(s)
         .filter_map(|line| {
             if line.is_empty() {
                 None
@@ -30,9 +656,34 @@ pub fn read_file_lines_extract_first(path: impl AsRef<Path>) -> (String, Vec<Str
             }
         })
         .collect::<Vec<_>>();
+    if input.is_empty() {
+        return Err(Error::EmptyInput);
+    }
     let first = input.remove(0);
 
-    (first, input)
+    Ok((first, input))
+}
+
+/// Parses a single line of values separated by `separator`, like `3,4,3,1,2`, reporting the
+/// column of the value that could not be parsed.
+pub fn try_parse_separated_as<T, E: Into<LineError>>(
+    line: &str,
+    separator: char,
+    f: impl Fn(&str) -> Result<T, E>,
+) -> Result<Vec<T>, LineError> {
+    let mut column = 1;
+    line.split(separator)
+        .map(|value| {
+            let result = f(value).map_err(|e| {
+                let e = e.into();
+                // point at the start of the value unless the parser knows better
+                let column = e.column.map_or(column, |c| column + c - 1);
+                LineError::at(column, e.reason)
+            });
+            column += value.chars().count() + 1;
+            result
+        })
+        .collect()
 }
 
 pub fn vec_to_array<T, const N: usize>(v: Vec<T>) -> [T; N] {
@@ -40,44 +691,430 @@ pub fn vec_to_array<T, const N: usize>(v: Vec<T>) -> [T; N] {
         .unwrap_or_else(|v: Vec<T>| panic!("Expected a Vec of length {} but it was {}", N, v.len()))
 }
 
-pub fn print_2d_array<T: Clone + Display>(array: &Array2D<T>) {
-    for row in array.rows_iter() {
-        for column in row.into_iter() {
-            print!("{}", column);
-        }
-        println!();
+/// Formats the array a row per line, ready to be logged.
+pub fn format_2d_array<T: Clone + Display>(array: &Array2D<T>) -> String {
+    array
+        .rows_iter()
+        .map(|row| row.map(|column| column.to_string()).collect::<String>() + "\n")
+        .collect()
+}
+
+pub fn format_u8_2d_array_with_delim(array: &Array2D<u8>) -> String {
+    array
+        .rows_iter()
+        .map(|row| {
+            let columns = row.map(|column| format!("{:2}|", column));
+            "|".to_string() + &columns.collect::<String>() + "\n"
+        })
+        .collect()
+}
+
+pub fn format_usize_2d_array_with_delim(array: &Array2D<usize>) -> String {
+    array
+        .rows_iter()
+        .map(|row| {
+            let columns = row.map(|column| format!("{:5}|", column));
+            "|".to_string() + &columns.collect::<String>() + "\n"
+        })
+        .collect()
+}
+
+pub fn parse_2d_number_grid(s: &str) -> Array2D<u8> {
+    try_parse_2d_number_grid(s).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_parse_2d_number_grid(s: &str) -> Result<Array2D<u8>, Error> {
+    try_parse_2d_grid(s, |c| {
+        c.to_digit(10)
+            .map(|d| d as u8)
+            .ok_or_else(|| format!("expected a digit, found '{}'", c))
+    })
+}
+
+/// Parses a rectangular map of characters, converting each of them with `f`.
+pub fn try_parse_2d_grid<T: Clone>(
+    s: &str,
+    f: impl Fn(char) -> Result<T, String>,
+) -> Result<Array2D<T>, Error> {
+    let elements = try_parse_lines_as(s, |l| {
+        l.chars()
+            .enumerate()
+            .map(|(i, c)| f(c).map_err(|reason| LineError::at(i + 1, reason)))
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+
+    let width = elements.first().ok_or(Error::EmptyInput)?.len();
+    if let Some((i, row)) = elements.iter().enumerate().find(|(_, r)| r.len() != width) {
+        return Err(Error::malformed(
+            i + 1,
+            format!("expected {} columns, found {}", width, row.len()),
+        ));
+    }
+
+    Ok(Array2D::from_rows(&elements).unwrap())
+}
+
+/// The `(x, y)` position of a tile in a [`Grid`], with `y` growing downwards.
+pub type Coords = (usize, usize);
+
+const NEIGHBOURS_4: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+const NEIGHBOURS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// A rectangular map of tiles addressed by `(x, y)` coordinates. By default, stepping over an edge
+/// leaves the grid; a wrapping grid is toroidal instead, so stepping over an edge re-enters on the
+/// opposite side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    tiles: Array2D<T>,
+    wrapping: bool,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn filled_with(value: T, width: usize, height: usize) -> Self {
+        Self::from(Array2D::filled_with(value, height, width))
+    }
+
+    /// Parses a rectangular map of characters, converting each of them with `f`.
+    pub fn parse(s: &str, f: impl Fn(char) -> Result<T, String>) -> Result<Self, Error> {
+        try_parse_2d_grid(s, f).map(Self::from)
     }
 }
 
-pub fn print_u8_2d_array_with_delim(array: &Array2D<u8>) {
-    for row in array.rows_iter() {
-        print!("|");
-        for column in row.into_iter() {
-            print!("{:2}|", column);
+impl Grid<u8> {
+    /// Parses a rectangular map of single digits, like the height map of day 9.
+    pub fn parse_digits(s: &str) -> Result<Self, Error> {
+        try_parse_2d_number_grid(s).map(Self::from)
+    }
+}
+
+impl<T> Grid<T> {
+    /// Makes the grid toroidal, so its neighbourhoods wrap around the edges.
+    pub fn wrapping(self) -> Self {
+        Self {
+            wrapping: true,
+            ..self
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.tiles.num_columns()
+    }
+
+    pub fn height(&self) -> usize {
+        self.tiles.num_rows()
+    }
+
+    pub fn get(&self, (x, y): Coords) -> Option<&T> {
+        self.tiles.get(y, x)
+    }
+
+    pub fn get_mut(&mut self, (x, y): Coords) -> Option<&mut T> {
+        self.tiles.get_mut(y, x)
+    }
+
+    /// Returns the tile at a position that may lie outside of the grid, in which case `default` is
+    /// returned, unless the grid is wrapping.
+    pub fn get_or<'a>(&'a self, x: isize, y: isize, default: &'a T) -> &'a T {
+        self.resolve(x, y)
+            .and_then(|coords| self.get(coords))
+            .unwrap_or(default)
+    }
+
+    pub fn set(&mut self, coords: Coords, value: T) {
+        self[coords] = value;
+    }
+
+    /// Returns the position `(dx, dy)` away from `coords`, or `None` if it lies outside of a grid
+    /// that is not wrapping.
+    pub fn offset(&self, (x, y): Coords, (dx, dy): (isize, isize)) -> Option<Coords> {
+        self.resolve(x as isize + dx, y as isize + dy)
+    }
+
+    fn resolve(&self, x: isize, y: isize) -> Option<Coords> {
+        let (width, height) = (self.width() as isize, self.height() as isize);
+        // an empty grid has no tile to wrap around to, so every position lies outside of it
+        if self.wrapping && width > 0 && height > 0 {
+            Some((x.rem_euclid(width) as usize, y.rem_euclid(height) as usize))
+        } else if (0..width).contains(&x) && (0..height).contains(&y) {
+            Some((x as usize, y as usize))
+        } else {
+            None
         }
-        println!();
+    }
+
+    /// The orthogonally adjacent positions of `coords`.
+    pub fn neighbours4(&self, coords: Coords) -> impl Iterator<Item = Coords> + '_ {
+        NEIGHBOURS_4
+            .iter()
+            .filter_map(move |&delta| self.offset(coords, delta))
+    }
+
+    /// The orthogonally and diagonally adjacent positions of `coords`.
+    pub fn neighbours8(&self, coords: Coords) -> impl Iterator<Item = Coords> + '_ {
+        NEIGHBOURS_8
+            .iter()
+            .filter_map(move |&delta| self.offset(coords, delta))
+    }
+
+    /// All positions of the grid in row-major order.
+    pub fn coords(&self) -> impl Iterator<Item = Coords> {
+        let width = self.width();
+        (0..self.height()).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// All tiles of the grid along with their positions, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (Coords, &T)> {
+        self.tiles
+            .enumerate_row_major()
+            .map(|((y, x), value)| ((x, y), value))
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.tiles.elements_row_major_iter()
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.tiles.elements_row_major_iter_mut()
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        self.tiles.rows_iter()
+    }
+
+    pub fn as_array(&self) -> &Array2D<T> {
+        &self.tiles
     }
 }
 
-pub fn print_usize_2d_array_with_delim(array: &Array2D<usize>) {
-    for row in array.rows_iter() {
-        print!("|");
-        for column in row.into_iter() {
-            print!("{:5}|", column);
+impl<T> From<Array2D<T>> for Grid<T> {
+    fn from(tiles: Array2D<T>) -> Self {
+        Self {
+            tiles,
+            wrapping: false,
         }
-        println!();
     }
 }
 
-pub fn parse_2d_number_grid(s: &str) -> Array2D<u8> {
-    let elements = &*s
-        .lines()
-        .map(|l| {
-            l.chars()
-                .map(|d| d.to_digit(10).unwrap() as u8)
-                .collect::<Vec<_>>()
+impl<T> Index<Coords> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, coords: Coords) -> &Self::Output {
+        self.get(coords).unwrap_or_else(|| {
+            panic!(
+                "{:?} is outside of the {}x{} grid",
+                coords,
+                self.width(),
+                self.height()
+            )
         })
-        .collect::<Vec<_>>();
+    }
+}
+
+impl<T> IndexMut<Coords> for Grid<T> {
+    fn index_mut(&mut self, coords: Coords) -> &mut Self::Output {
+        let (width, height) = (self.width(), self.height());
+        self.get_mut(coords)
+            .unwrap_or_else(|| panic!("{:?} is outside of the {}x{} grid", coords, width, height))
+    }
+}
 
-    Array2D::from_rows(elements).unwrap()
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows() {
+            for value in row {
+                write!(f, "{}", value)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEPS: Param = Param {
+        name: "steps",
+        default: 100,
+        description: "the steps to take",
+        check: |steps| Param::in_range(steps, 1..=1000),
+    };
+
+    #[test]
+    fn params_fall_back_to_their_defaults() {
+        assert_eq!(Params::default().get(&STEPS), 100);
+        assert_eq!(
+            Params::parse(&[STEPS], &["steps=5"]).unwrap().get(&STEPS),
+            5
+        );
+    }
+
+    #[test]
+    fn params_reject_unknown_names_and_bad_values() {
+        assert!(Params::parse(&[STEPS], &["days=5"]).is_err());
+        assert!(Params::parse(&[STEPS], &["steps=-1"]).is_err());
+        assert!(Params::parse(&[STEPS], &["steps"]).is_err());
+        assert!(Params::parse(&[], &["steps=5"]).is_err());
+    }
+
+    #[test]
+    fn params_reject_values_their_check_rejects() {
+        assert_eq!(
+            Params::parse(&[STEPS], &["steps=0"]).unwrap_err(),
+            "'0' is not a valid value for 'steps', expected 1 to 1000"
+        );
+        assert!(Params::parse(&[STEPS], &["steps=1001"]).is_err());
+        assert!(Params::parse(&[STEPS], &["steps=1000"]).is_ok());
+        assert_eq!(
+            Param::in_range(0, 1..=usize::MAX),
+            Err("expected at least 1".to_string())
+        );
+    }
+
+    #[test]
+    fn log_levels_parse_and_grow_in_verbosity() {
+        assert_eq!("quiet".parse(), Ok(LogLevel::Quiet));
+        assert_eq!("trace".parse(), Ok(LogLevel::Trace));
+        assert!("verbose".parse::<LogLevel>().is_err());
+        assert!(LogLevel::Quiet < LogLevel::Info);
+        assert!(LogLevel::Debug < LogLevel::Trace);
+    }
+
+    #[test]
+    fn split_blocks_on_blank_lines() {
+        let blocks = split_blocks("a\nb\n\n\nc\n");
+        assert_eq!(
+            blocks,
+            vec![
+                Block {
+                    first_line: 1,
+                    lines: vec!["a", "b"]
+                },
+                Block {
+                    first_line: 5,
+                    lines: vec!["c"]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn split_blocks_with_mixed_line_endings() {
+        let blocks = split_blocks("a \r\nb\n\r\nc\r\n");
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].lines, vec!["a", "b"]);
+        assert_eq!(blocks[1].text(), "c");
+    }
+
+    #[test]
+    fn block_parse_with_reports_line_in_whole_input() {
+        let blocks = split_blocks("1\n\n2\nx\n");
+        let e = blocks[1]
+            .parse_with(|s| try_parse_lines_as(s, |l| l.parse::<u32>().map_err(|e| e.to_string())))
+            .unwrap_err();
+        assert_eq!(e.to_string(), "line 4: invalid digit found in string");
+    }
+
+    #[test]
+    fn separated_values_report_column() {
+        let parse = |v: &str| {
+            v.parse::<u8>()
+                .map_err(|_| format!("'{}' is not a number", v))
+        };
+        assert_eq!(
+            try_parse_separated_as("3,4,3", ',', parse),
+            Ok(vec![3, 4, 3])
+        );
+        assert_eq!(
+            try_parse_separated_as("3,4,x1", ',', parse),
+            Err(LineError::at(5, "'x1' is not a number"))
+        );
+    }
+
+    #[test]
+    fn number_grid() {
+        let grid = try_parse_2d_number_grid("12\n34\n").unwrap();
+        assert_eq!(grid.get(1, 0), Some(&3));
+        assert_eq!(
+            try_parse_2d_number_grid("12\n3x\n")
+                .unwrap_err()
+                .to_string(),
+            "line 2, column 2: expected a digit, found 'x'"
+        );
+        assert_eq!(
+            try_parse_2d_number_grid("12\n345\n")
+                .unwrap_err()
+                .to_string(),
+            "line 2: expected 2 columns, found 3"
+        );
+    }
+
+    #[test]
+    fn extract_first_line() {
+        let (first, rest) = try_parse_lines_extract_first("NNCB\n\nCH -> B\n").unwrap();
+        assert_eq!(first, "NNCB");
+        assert_eq!(rest, vec!["CH -> B"]);
+        assert!(matches!(
+            try_parse_lines_extract_first("\n"),
+            Err(Error::EmptyInput)
+        ));
+    }
+
+    #[test]
+    fn explicit_input_path_wins() {
+        assert_eq!(resolve_input_path(3, Some("-")), PathBuf::from("-"));
+    }
+
+    #[test]
+    fn grid_neighbours_stop_at_edges() {
+        let grid = Grid::parse_digits("123\n456\n").unwrap();
+        assert_eq!(grid[(2, 1)], 6);
+        assert_eq!(
+            grid.neighbours4((0, 0)).collect::<Vec<_>>(),
+            vec![(1, 0), (0, 1)]
+        );
+        assert_eq!(grid.neighbours8((1, 0)).count(), 5);
+        assert_eq!(grid.neighbours8((1, 1)).count(), 5);
+    }
+
+    #[test]
+    fn wrapping_grid_neighbours() {
+        let grid = Grid::filled_with(0, 3, 2).wrapping();
+        assert_eq!(
+            grid.neighbours4((0, 0)).collect::<Vec<_>>(),
+            vec![(0, 1), (2, 0), (1, 0), (0, 1)]
+        );
+        assert_eq!(grid.offset((2, 1), (1, 1)), Some((0, 0)));
+        assert_eq!(grid.neighbours8((1, 1)).count(), 8);
+    }
+
+    #[test]
+    fn empty_wrapping_grid_has_no_neighbours() {
+        for (width, height) in [(0, 0), (3, 0), (0, 2)] {
+            let grid = Grid::filled_with(0, width, height).wrapping();
+            assert_eq!(grid.neighbours8((0, 0)).count(), 0);
+            assert_eq!(grid.offset((0, 0), (1, 1)), None);
+            assert_eq!(*grid.get_or(-1, 1, &7), 7);
+        }
+    }
+
+    #[test]
+    fn grid_default_outside_of_bounds() {
+        let grid = Grid::parse("#.\n..\n", |c| Ok(c == '#')).unwrap();
+        assert!(*grid.get_or(0, 0, &false));
+        assert!(*grid.get_or(-1, 5, &true));
+        assert!(!*grid.get_or(1, 1, &true));
+        assert_eq!(grid.to_string(), "truefalse\nfalsefalse\n");
+    }
 }
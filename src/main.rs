@@ -0,0 +1,849 @@
+use advent_of_code_2021::{
+    answers::{Answers, Outcome, Summary, DEFAULT_ANSWERS_PATH},
+    bench::{bench_day, format_measurements, Format},
+    cache::{binary_version, Cache},
+    debug,
+    generate::{generate_input, DEFAULT_SEED, GENERATORS},
+    render::{animate_day, write_frames, ImageFormat, Palette, ANIMATED_DAYS},
+    resolve_input_path, set_log_level,
+    submissions::{Submissions, DEFAULT_SUBMISSIONS_PATH},
+    try_read_file_to_string,
+    website::{fetch_input, Config, Fetched, Verdict, Website},
+    DynSolution, Error, LogLevel, Params, ParsedInput, Part, DAYS,
+};
+use serde_json::json;
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, Write},
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+    process,
+    time::{Duration, Instant},
+};
+
+const USAGE: &str = "\
+Usage: aoc run <day>|all [--part 1|2] [--input <path>|-] [--format text|json] [--output <path>]
+                         [--check [--answers <path>]] [--param <name>=<value>]...
+                         [--no-cache] [--clear-cache]
+       aoc bench <day>|all [--iterations <n>] [--input <path>|-] [--format text|json|csv]
+                           [--output <path>] [--param <name>=<value>]...
+                           [--generate <size> [--seed <n>]]
+       aoc params <day>|all
+       aoc fetch <day>
+       aoc submit <day> <part> [--input <path>|-] [--answer <answer>] [--submissions <path>]
+       aoc render <day> [--input <path>|-] [--format ppm|pgm|gif] [--palette <colours>]
+                        [--scale <n>] [--delay <centiseconds>] [--output <path>]
+       aoc generate <day> [--size <n>] [--seed <n>] [--output <path>]
+
+Every command also takes [--log quiet|info|debug|trace], which defaults to info, or to quiet when
+benchmarking.";
+
+const DEFAULT_ITERATIONS: usize = 10;
+const DEFAULT_DELAY: u16 = 10;
+
+fn parse_day(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(day) if (1..=DAYS.len()).contains(&day) => Ok(day),
+        _ => Err(format!(
+            "'{}' is not a valid day, expected 1-{}",
+            s,
+            DAYS.len()
+        )),
+    }
+}
+
+fn parse_days(s: &str) -> Result<Vec<usize>, String> {
+    match s {
+        "all" => Ok((1..=DAYS.len()).collect()),
+        day => Ok(vec![parse_day(day)?]),
+    }
+}
+
+fn parse_part(s: &str) -> Result<Part, String> {
+    match s {
+        "1" => Ok(Part::One),
+        "2" => Ok(Part::Two),
+        _ => Err(format!("'{}' is not a valid part, expected 1 or 2", s)),
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum AnswerFormat {
+    Text,
+    /// One JSON object per line, so tools can consume the answers without parsing prose.
+    Json,
+}
+
+fn parse_answer_format(s: &str) -> Result<AnswerFormat, String> {
+    match s {
+        "text" => Ok(AnswerFormat::Text),
+        "json" => Ok(AnswerFormat::Json),
+        _ => Err(format!(
+            "'{}' is not a valid format, expected text or json",
+            s
+        )),
+    }
+}
+
+fn write_answer(
+    out: &mut dyn Write,
+    format: AnswerFormat,
+    day: usize,
+    part: Part,
+    answer: &str,
+    cached: bool,
+    elapsed: Duration,
+) -> io::Result<()> {
+    match format {
+        // multi-line answers, like the folded paper of day 13, start on their own line
+        AnswerFormat::Text if answer.contains('\n') => {
+            writeln!(out, "Part {}:\n{}", part, answer.trim_end())
+        }
+        AnswerFormat::Text => writeln!(out, "Part {}: {}", part, answer),
+        AnswerFormat::Json => {
+            let record = json!({
+                "day": day,
+                "part": part.number(),
+                "answer": answer.trim_end(),
+                "cached": cached,
+                "elapsed": elapsed.as_secs_f64(),
+            });
+            writeln!(out, "{}", record)
+        }
+    }
+}
+
+fn write_error(
+    out: &mut dyn Write,
+    format: AnswerFormat,
+    day: usize,
+    part: Part,
+    error: &str,
+) -> io::Result<()> {
+    match format {
+        // errors always go to stderr, the text output has nothing to add
+        AnswerFormat::Text => Ok(()),
+        AnswerFormat::Json => {
+            let record = json!({
+                "day": day,
+                "part": part.number(),
+                "error": error,
+            });
+            writeln!(out, "{}", record)
+        }
+    }
+}
+
+/// Parses the `--param` assignments, which only make sense for a single day.
+fn parse_params(days: &[usize], assignments: &[&str]) -> Result<Params, String> {
+    match days {
+        _ if assignments.is_empty() => Ok(Params::default()),
+        [day] => Params::parse(DAYS[day - 1].params(), assignments),
+        _ => Err("'--param' can only be used with a single day".to_string()),
+    }
+}
+
+fn parts_of(solution: &dyn DynSolution, part: Option<Part>) -> Vec<Part> {
+    match part {
+        Some(Part::Two) if !solution.has_part2() => vec![],
+        Some(part) => vec![part],
+        None if solution.has_part2() => vec![Part::One, Part::Two],
+        None => vec![Part::One],
+    }
+}
+
+fn run_days(args: &[String]) -> Result<(), String> {
+    let mut args = args.iter();
+    let days = parse_days(args.next().ok_or("Missing day")?)?;
+
+    let mut part = None;
+    let mut input = None;
+    let mut format = AnswerFormat::Text;
+    let mut output = None;
+    let mut check = false;
+    let mut answers_path = None;
+    let mut assignments = Vec::new();
+    let mut use_cache = true;
+    let mut clear_cache = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--part" => {
+                let value = args.next().ok_or("Missing value for '--part'")?;
+                part = Some(parse_part(value)?);
+            }
+            "--input" => {
+                let value = args.next().ok_or("Missing value for '--input'")?;
+                input = Some(value.as_str());
+            }
+            "--format" => {
+                let value = args.next().ok_or("Missing value for '--format'")?;
+                format = parse_answer_format(value)?;
+            }
+            "--output" => {
+                let value = args.next().ok_or("Missing value for '--output'")?;
+                output = Some(value.as_str());
+            }
+            "--check" => check = true,
+            "--answers" => {
+                let value = args.next().ok_or("Missing value for '--answers'")?;
+                answers_path = Some(value.as_str());
+            }
+            "--param" => {
+                let value = args.next().ok_or("Missing value for '--param'")?;
+                assignments.push(value.as_str());
+            }
+            "--no-cache" => use_cache = false,
+            "--clear-cache" => clear_cache = true,
+            _ => return Err(format!("Unexpected argument '{}'", arg)),
+        }
+    }
+
+    if input.is_some() && days.len() > 1 {
+        return Err("'--input' can only be used with a single day".to_string());
+    }
+    if answers_path.is_some() && !check {
+        return Err("'--answers' can only be used with '--check'".to_string());
+    }
+    if check && (format != AnswerFormat::Text || output.is_some()) {
+        return Err("'--format' and '--output' cannot be used with '--check'".to_string());
+    }
+    if check && !assignments.is_empty() {
+        // the recorded answers are those of the puzzle itself
+        return Err("'--param' cannot be used with '--check'".to_string());
+    }
+    let params = parse_params(&days, &assignments)?;
+    if let [day] = days[..] {
+        if part == Some(Part::Two) && !DAYS[day - 1].has_part2() {
+            return Err(format!("Day {} has no second part", day));
+        }
+    }
+
+    if clear_cache {
+        let dir = Cache::default_dir();
+        if let Err(e) = Cache::clear(&dir) {
+            eprintln!("Could not clear '{}': {}", dir.display(), e);
+            process::exit(1);
+        }
+    }
+
+    // a broken input or a wrong answer is not a usage error, so it is reported without the usage
+    // line
+    let succeeded = if check {
+        let answers_path = answers_path.unwrap_or(DEFAULT_ANSWERS_PATH);
+        match Answers::load(answers_path) {
+            // the answers being checked are those of the solutions, never of the cache
+            Ok(answers) => check_days(&days, part, input, &answers),
+            Err(e) => {
+                eprintln!("{}", e);
+                false
+            }
+        }
+    } else {
+        let mut out: Box<dyn Write> = match output {
+            Some(path) => match File::create(path) {
+                Ok(file) => Box::new(file),
+                Err(e) => {
+                    eprintln!("Could not create '{}': {}", path, e);
+                    process::exit(1);
+                }
+            },
+            None => Box::new(io::stdout()),
+        };
+        // hashing the executable for the version takes a while, so only when the cache is used
+        let cache = if use_cache {
+            Some(Cache::new(Cache::default_dir(), binary_version()))
+        } else {
+            None
+        };
+        solve_days(
+            &days,
+            part,
+            input,
+            &params,
+            cache.as_ref(),
+            format,
+            &mut out,
+        )
+        .unwrap_or_else(|e| {
+            eprintln!("Could not write the answers: {}", e);
+            false
+        })
+    };
+    if !succeeded {
+        process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn describe_input_error(path: &Path, e: Error) -> String {
+    match e {
+        Error::Io { .. } => e.to_string(),
+        e => format!("Could not parse '{}': {}", path.display(), e),
+    }
+}
+
+/// Reads and parses the input at `path`, returning the raw input along with the parsed one.
+fn parse_input<'a>(
+    solution: &'a dyn DynSolution,
+    path: &Path,
+    params: &Params,
+) -> Result<(Box<dyn ParsedInput + 'a>, String), String> {
+    try_read_file_to_string(path)
+        .and_then(|input| Ok((solution.parse_with(&input, params.clone())?, input)))
+        .map_err(|e| describe_input_error(path, e))
+}
+
+fn solve_days(
+    days: &[usize],
+    part: Option<Part>,
+    input: Option<&str>,
+    params: &Params,
+    cache: Option<&Cache>,
+    format: AnswerFormat,
+    out: &mut dyn Write,
+) -> io::Result<bool> {
+    let mut succeeded = true;
+    for &day in days {
+        let solution = DAYS[day - 1];
+        let parts = parts_of(solution, part);
+        if parts.is_empty() {
+            continue;
+        }
+        if days.len() > 1 && format == AnswerFormat::Text {
+            writeln!(out, "Day {}:", day)?;
+        }
+
+        let path = resolve_input_path(day, input);
+        match parse_input(solution, &path, params) {
+            Ok((parsed, input)) => {
+                for &part in &parts {
+                    let start = Instant::now();
+                    let (answer, cached) =
+                        solve_or_recall(parsed.as_ref(), cache, day, part, &input, params);
+                    write_answer(out, format, day, part, &answer, cached, start.elapsed())?;
+                }
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                for &part in &parts {
+                    write_error(out, format, day, part, &e)?;
+                }
+                succeeded = false;
+            }
+        }
+    }
+
+    Ok(succeeded)
+}
+
+/// Takes the answer of `part` from the cache if it is there, and solves it otherwise, caching the
+/// answer; also returns whether the answer came from the cache. An answer that cannot be cached is
+/// still an answer, so failing to cache it is only reported.
+fn solve_or_recall(
+    parsed: &dyn ParsedInput,
+    cache: Option<&Cache>,
+    day: usize,
+    part: Part,
+    input: &str,
+    params: &Params,
+) -> (String, bool) {
+    if let Some(answer) = cache.and_then(|cache| cache.get(day, part, input, params)) {
+        debug!("Using the cached answer of day {} part {}", day, part);
+        return (answer, true);
+    }
+
+    let answer = parsed.solve(part);
+    if let Some(cache) = cache {
+        if let Err(e) = cache.insert(day, part, input, params, &answer) {
+            eprintln!(
+                "Could not cache the answer in '{}': {}",
+                cache.dir().display(),
+                e
+            );
+        }
+    }
+    (answer, false)
+}
+
+fn check_days(days: &[usize], part: Option<Part>, input: Option<&str>, answers: &Answers) -> bool {
+    let mut summary = Summary::default();
+    for &day in days {
+        let solution = DAYS[day - 1];
+        let path = resolve_input_path(day, input);
+        check_day(
+            day,
+            solution,
+            &path,
+            &parts_of(solution, part),
+            answers,
+            &mut summary,
+        );
+    }
+    println!("{}", summary);
+
+    !summary.has_regressions()
+}
+
+fn check_day(
+    day: usize,
+    solution: &dyn DynSolution,
+    path: &Path,
+    parts: &[Part],
+    answers: &Answers,
+    summary: &mut Summary,
+) {
+    // a panicking solution is a regression like any other, and must not stop the remaining days
+    // from being checked
+    let parsed = panic::catch_unwind(AssertUnwindSafe(|| {
+        parse_input(solution, path, &Params::default()).map(|(parsed, _)| parsed)
+    }))
+    .unwrap_or_else(|_| Err(format!("Parsing '{}' panicked", path.display())));
+    for &part in parts {
+        let outcome = match &parsed {
+            Ok(parsed) => match panic::catch_unwind(AssertUnwindSafe(|| parsed.solve(part))) {
+                Ok(answer) => answers.check(day, part, &answer),
+                Err(_) => Outcome::Fail("the solution panicked".to_string()),
+            },
+            Err(e) => Outcome::Fail(e.clone()),
+        };
+        println!("Day {} part {}: {}", day, part, outcome);
+        summary.record(&outcome);
+    }
+}
+
+fn bench_days(args: &[String]) -> Result<(), String> {
+    let mut args = args.iter();
+    let days = parse_days(args.next().ok_or("Missing day")?)?;
+
+    let mut iterations = DEFAULT_ITERATIONS;
+    let mut input = None;
+    let mut format = Format::Text;
+    let mut output = None;
+    let mut assignments = Vec::new();
+    let mut size = None;
+    let mut seed = DEFAULT_SEED;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--iterations" => {
+                let value = args.next().ok_or("Missing value for '--iterations'")?;
+                iterations = match value.parse::<usize>() {
+                    Ok(iterations) if iterations > 0 => iterations,
+                    _ => return Err(format!("'{}' is not a valid number of iterations", value)),
+                };
+            }
+            "--input" => {
+                let value = args.next().ok_or("Missing value for '--input'")?;
+                input = Some(value.as_str());
+            }
+            "--format" => {
+                let value = args.next().ok_or("Missing value for '--format'")?;
+                format = value.parse()?;
+            }
+            "--output" => {
+                let value = args.next().ok_or("Missing value for '--output'")?;
+                output = Some(value.as_str());
+            }
+            "--param" => {
+                let value = args.next().ok_or("Missing value for '--param'")?;
+                assignments.push(value.as_str());
+            }
+            "--generate" => {
+                let value = args.next().ok_or("Missing value for '--generate'")?;
+                size = Some(
+                    value
+                        .parse::<usize>()
+                        .map_err(|_| format!("'{}' is not a valid size", value))?,
+                );
+            }
+            "--seed" => {
+                let value = args.next().ok_or("Missing value for '--seed'")?;
+                seed = value
+                    .parse()
+                    .map_err(|_| format!("'{}' is not a valid seed", value))?;
+            }
+            _ => return Err(format!("Unexpected argument '{}'", arg)),
+        }
+    }
+
+    if input.is_some() && days.len() > 1 {
+        return Err("'--input' can only be used with a single day".to_string());
+    }
+    if size.is_some() && (input.is_some() || days.len() > 1) {
+        return Err(
+            "'--generate' can only be used with a single day, and without '--input'".to_string(),
+        );
+    }
+    let params = parse_params(&days, &assignments)?;
+
+    let mut measurements = Vec::new();
+    let mut succeeded = true;
+    for &day in &days {
+        let path = resolve_input_path(day, input);
+        // a generated input is the same for the same size and seed, so it can be benchmarked
+        // before and after a change, at a size the real input does not come close to
+        let result = match size {
+            Some(size) => bench_day(
+                day,
+                DAYS[day - 1],
+                &generate_input(day, Some(size), seed),
+                &params,
+                iterations,
+            )
+            .map_err(|e| format!("Could not parse the generated input: {}", e)),
+            None => try_read_file_to_string(&path)
+                .and_then(|input| bench_day(day, DAYS[day - 1], &input, &params, iterations))
+                .map_err(|e| describe_input_error(&path, e)),
+        };
+        match result {
+            Ok(mut day_measurements) => measurements.append(&mut day_measurements),
+            Err(e) => {
+                eprintln!("{}", e);
+                succeeded = false;
+            }
+        }
+    }
+
+    // the report is written to a file on request, so solutions printing their progress do not
+    // end up in the middle of it
+    let report = format_measurements(&measurements, format);
+    match output {
+        Some(path) => {
+            if let Err(e) = fs::write(path, report) {
+                eprintln!("Could not write '{}': {}", path, e);
+                succeeded = false;
+            }
+        }
+        None => print!("{}", report),
+    }
+    if !succeeded {
+        process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn render_day(args: &[String]) -> Result<(), String> {
+    let mut args = args.iter();
+    let day = parse_day(args.next().ok_or("Missing day")?)?;
+    if !ANIMATED_DAYS.contains(&day) {
+        return Err(format!(
+            "Day {} has nothing to render, expected one of {:?}",
+            day, ANIMATED_DAYS
+        ));
+    }
+
+    let mut input = None;
+    let mut format = ImageFormat::Ppm;
+    let mut palette = None;
+    let mut scale = None;
+    let mut delay = DEFAULT_DELAY;
+    let mut output = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => {
+                let value = args.next().ok_or("Missing value for '--input'")?;
+                input = Some(value.as_str());
+            }
+            "--format" => {
+                let value = args.next().ok_or("Missing value for '--format'")?;
+                format = value.parse()?;
+            }
+            "--palette" => {
+                let value = args.next().ok_or("Missing value for '--palette'")?;
+                palette = Some(value.parse::<Palette>()?);
+            }
+            "--scale" => {
+                let value = args.next().ok_or("Missing value for '--scale'")?;
+                scale = match value.parse::<usize>() {
+                    Ok(scale) if scale > 0 => Some(scale),
+                    _ => return Err(format!("'{}' is not a valid scale", value)),
+                };
+            }
+            "--delay" => {
+                let value = args.next().ok_or("Missing value for '--delay'")?;
+                delay = value
+                    .parse()
+                    .map_err(|_| format!("'{}' is not a valid delay", value))?;
+            }
+            "--output" => {
+                let value = args.next().ok_or("Missing value for '--output'")?;
+                output = Some(value.clone());
+            }
+            _ => return Err(format!("Unexpected argument '{}'", arg)),
+        }
+    }
+
+    let path = resolve_input_path(day, input);
+    let animation = try_read_file_to_string(&path)
+        .and_then(|input| animate_day(day, &input).unwrap())
+        .map_err(|e| describe_input_error(&path, e));
+    let animation = match animation {
+        Ok(animation) => animation,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+
+    let scale = scale.unwrap_or(animation.scale);
+    let frames = animation
+        .frames
+        .iter()
+        .map(|frame| frame.scaled(scale))
+        .collect::<Vec<_>>();
+    let output = output.unwrap_or_else(|| match format {
+        ImageFormat::Gif => format!("day{}.gif", day),
+        ImageFormat::Ppm | ImageFormat::Pgm => format!("frames/day{}", day),
+    });
+    let palette = palette.unwrap_or(animation.palette);
+    if let Err(e) = write_frames(Path::new(&output), &frames, &palette, format, delay) {
+        eprintln!("Could not write '{}': {}", output, e);
+        process::exit(1);
+    }
+    eprintln!("Rendered {} frames to '{}'", frames.len(), output);
+
+    Ok(())
+}
+
+fn generate_day(args: &[String]) -> Result<(), String> {
+    let mut args = args.iter();
+    let day = parse_day(args.next().ok_or("Missing day")?)?;
+
+    let mut size = None;
+    let mut seed = DEFAULT_SEED;
+    let mut output = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--size" => {
+                let value = args.next().ok_or("Missing value for '--size'")?;
+                size = Some(value.parse::<usize>().map_err(|_| {
+                    format!(
+                        "'{}' is not a valid size, expected the number of {}",
+                        value,
+                        GENERATORS[day - 1].size
+                    )
+                })?);
+            }
+            "--seed" => {
+                let value = args.next().ok_or("Missing value for '--seed'")?;
+                seed = value
+                    .parse()
+                    .map_err(|_| format!("'{}' is not a valid seed", value))?;
+            }
+            "--output" => {
+                let value = args.next().ok_or("Missing value for '--output'")?;
+                output = Some(value.as_str());
+            }
+            _ => return Err(format!("Unexpected argument '{}'", arg)),
+        }
+    }
+
+    let input = generate_input(day, size, seed);
+    match output {
+        Some(path) => {
+            if let Err(e) = fs::write(path, input) {
+                eprintln!("Could not write '{}': {}", path, e);
+                process::exit(1);
+            }
+        }
+        None => print!("{}", input),
+    }
+
+    Ok(())
+}
+
+fn fetch_day(args: &[String]) -> Result<(), String> {
+    let (day, rest) = args.split_first().ok_or("Missing day")?;
+    let day = parse_day(day)?;
+    if let Some(arg) = rest.first() {
+        return Err(format!("Unexpected argument '{}'", arg));
+    }
+
+    match Config::load().and_then(|config| fetch_input(&config, day)) {
+        Ok(Fetched::Downloaded(path)) => {
+            eprintln!(
+                "Downloaded the input of day {} to '{}'",
+                day,
+                path.display()
+            )
+        }
+        Ok(Fetched::Existing(path)) => {
+            eprintln!(
+                "'{}' exists already, it is not downloaded again",
+                path.display()
+            )
+        }
+        Err(e) => {
+            eprintln!("Could not fetch the input of day {}: {}", day, e);
+            process::exit(1);
+        }
+    }
+
+    Ok(())
+}
+
+fn submit_answer(args: &[String]) -> Result<(), String> {
+    let mut args = args.iter();
+    let day = parse_day(args.next().ok_or("Missing day")?)?;
+    let part = parse_part(args.next().ok_or("Missing part")?)?;
+
+    let mut input = None;
+    let mut answer = None;
+    let mut submissions_path = DEFAULT_SUBMISSIONS_PATH;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => {
+                let value = args.next().ok_or("Missing value for '--input'")?;
+                input = Some(value.as_str());
+            }
+            "--answer" => {
+                let value = args.next().ok_or("Missing value for '--answer'")?;
+                answer = Some(value.clone());
+            }
+            "--submissions" => {
+                let value = args.next().ok_or("Missing value for '--submissions'")?;
+                submissions_path = value.as_str();
+            }
+            _ => return Err(format!("Unexpected argument '{}'", arg)),
+        }
+    }
+
+    let solution = DAYS[day - 1];
+    if part == Part::Two && !solution.has_part2() {
+        return Err(format!("Day {} has no second part", day));
+    }
+    if answer.is_some() && input.is_some() {
+        return Err("'--input' cannot be used with '--answer'".to_string());
+    }
+
+    let answer = match answer {
+        Some(answer) => answer,
+        None => {
+            let path = resolve_input_path(day, input);
+            let params = Params::default();
+            let (parsed, input) = parse_input(solution, &path, &params).unwrap_or_else(|e| {
+                eprintln!("Day {}: {}", day, e);
+                process::exit(1);
+            });
+            let cache = Cache::new(Cache::default_dir(), binary_version());
+            solve_or_recall(parsed.as_ref(), Some(&cache), day, part, &input, &params).0
+        }
+    };
+    let answer = answer.trim_end();
+    if answer.contains('\n') {
+        // like the folded paper of day 13, which spells out the actual answer
+        eprintln!(
+            "The answer of day {} part {} has to be read off:\n{}\nPass what it reads with '--answer'",
+            day, part, answer
+        );
+        process::exit(1);
+    }
+
+    let mut submissions = Submissions::load(submissions_path).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+    if let Err(reason) = submissions.check(day, part, answer) {
+        eprintln!("Not submitting: {}", reason);
+        process::exit(1);
+    }
+
+    let verdict = Config::load()
+        .and_then(|config| Website::new(&config))
+        .and_then(|website| website.submit(day, part, answer))
+        .unwrap_or_else(|e| {
+            eprintln!("Could not submit day {} part {}: {}", day, part, e);
+            process::exit(1);
+        });
+    match verdict {
+        Verdict::Wait(wait) => println!(
+            "Day {} part {}: submitted too soon, wait {}s before submitting again",
+            day,
+            part,
+            wait.as_secs()
+        ),
+        Verdict::AlreadySolved => println!(
+            "Day {} part {}: already solved, or not unlocked yet",
+            day, part
+        ),
+        _ => println!("Day {} part {}: {} is {}", day, part, answer, verdict),
+    }
+
+    submissions.record(day, part, answer, verdict);
+    if let Err(e) = submissions.save(submissions_path) {
+        eprintln!("Could not record the verdict: {}", e);
+    }
+
+    if verdict != Verdict::Correct {
+        process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn list_params(args: &[String]) -> Result<(), String> {
+    let (days, rest) = args.split_first().ok_or("Missing day")?;
+    if let Some(arg) = rest.first() {
+        return Err(format!("Unexpected argument '{}'", arg));
+    }
+
+    for day in parse_days(days)? {
+        for param in DAYS[day - 1].params() {
+            println!(
+                "Day {}: {}={} ({})",
+                day, param.name, param.default, param.description
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Takes `--log <level>` out of the arguments, wherever it is, as every command accepts it.
+fn take_log_level(args: &mut Vec<String>) -> Result<Option<LogLevel>, String> {
+    match args.iter().position(|arg| arg == "--log") {
+        Some(i) => {
+            let value = args.get(i + 1).ok_or("Missing value for '--log'")?;
+            let level = value.parse()?;
+            args.drain(i..=i + 1);
+            Ok(Some(level))
+        }
+        None => Ok(None),
+    }
+}
+
+fn main() {
+    let mut args = env::args().skip(1).collect::<Vec<_>>();
+
+    let result = take_log_level(&mut args).and_then(|level| {
+        // logging would only skew the measurements
+        let default_level = match args.first() {
+            Some(command) if command == "bench" => LogLevel::Quiet,
+            _ => LogLevel::Info,
+        };
+        set_log_level(level.unwrap_or(default_level));
+        run_command(&args)
+    });
+
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    }
+}
+
+fn run_command(args: &[String]) -> Result<(), String> {
+    match args.split_first() {
+        Some((command, rest)) if command == "run" => run_days(rest),
+        Some((command, rest)) if command == "bench" => bench_days(rest),
+        Some((command, rest)) if command == "render" => render_day(rest),
+        Some((command, rest)) if command == "generate" => generate_day(rest),
+        Some((command, rest)) if command == "params" => list_params(rest),
+        Some((command, rest)) if command == "fetch" => fetch_day(rest),
+        Some((command, rest)) if command == "submit" => submit_answer(rest),
+        Some((command, _)) => Err(format!("Unknown command '{}'", command)),
+        None => Err("Missing command".to_string()),
+    }
+}
@@ -0,0 +1,404 @@
+use crate::{day11, day13, day20, day25, Coords, Error, Grid, Solution};
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+    str::FromStr,
+};
+
+pub type Rgb = [u8; 3];
+
+/// The colours a [`Frame`] is drawn with, indexed by the values of its pixels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    colours: Vec<Rgb>,
+}
+
+impl Palette {
+    pub fn new(colours: Vec<Rgb>) -> Self {
+        assert!(
+            (1..=256).contains(&colours.len()),
+            "a palette needs 1-256 colours, found {}",
+            colours.len()
+        );
+        Self { colours }
+    }
+
+    /// `n` colours fading evenly from `from` to `to`.
+    pub fn gradient(from: Rgb, to: Rgb, n: usize) -> Self {
+        let blend = |i: usize, channel: usize| {
+            let (from, to) = (from[channel] as usize, to[channel] as usize);
+            let steps = (n - 1).max(1);
+            ((from * (steps - i) + to * i) / steps) as u8
+        };
+        Self::new(
+            (0..n)
+                .map(|i| [blend(i, 0), blend(i, 1), blend(i, 2)])
+                .collect(),
+        )
+    }
+
+    /// Returns the colour of `index`, reusing the last colour for values past the end.
+    pub fn colour(&self, index: u8) -> Rgb {
+        self.colours[self.clamp(index) as usize]
+    }
+
+    /// Maps values past the end of the palette to its last colour, like `colour` does.
+    fn clamp(&self, index: u8) -> u8 {
+        index.min((self.colours.len() - 1).min(u8::MAX as usize) as u8)
+    }
+
+    pub fn colours(&self) -> &[Rgb] {
+        &self.colours
+    }
+}
+
+/// Parses a comma-separated list of `RRGGBB` colours, with or without a leading `#`.
+impl FromStr for Palette {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let colours = s
+            .split(',')
+            .map(|colour| {
+                let hex = colour.trim().trim_start_matches('#');
+                match u32::from_str_radix(hex, 16) {
+                    Ok(rgb) if hex.len() == 6 => {
+                        Ok([(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8])
+                    }
+                    _ => Err(format!("'{}' is not a colour of the form RRGGBB", colour)),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if colours.len() > 256 {
+            return Err(format!(
+                "a palette has at most 256 colours, found {}",
+                colours.len()
+            ));
+        }
+
+        Ok(Self::new(colours))
+    }
+}
+
+/// A single image of a simulation, where every pixel is an index into a [`Palette`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Frame {
+    pub fn from_fn(width: usize, height: usize, f: impl Fn(Coords) -> u8) -> Self {
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(f)
+            .collect();
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn from_grid<T>(grid: &Grid<T>, f: impl Fn(&T) -> u8) -> Self {
+        Self::from_fn(grid.width(), grid.height(), |coords| f(&grid[coords]))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixel(&self, (x, y): Coords) -> u8 {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Enlarges every pixel to a `factor`x`factor` square, so small grids are still visible.
+    pub fn scaled(&self, factor: usize) -> Self {
+        Self::from_fn(self.width * factor, self.height * factor, |(x, y)| {
+            self.pixel((x / factor, y / factor))
+        })
+    }
+
+    /// Places the frame in the middle of a larger canvas filled with `background`.
+    pub fn centered_in(&self, width: usize, height: usize, background: u8) -> Self {
+        let left = (width - self.width) / 2;
+        let top = (height - self.height) / 2;
+        Self::from_fn(width, height, |(x, y)| {
+            let inside =
+                (left..left + self.width).contains(&x) && (top..top + self.height).contains(&y);
+            if inside {
+                self.pixel((x - left, y - top))
+            } else {
+                background
+            }
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// A separate binary PPM image per frame.
+    Ppm,
+    /// A separate binary PGM image per frame, with the palette converted to grayscale.
+    Pgm,
+    /// A single animated GIF of all frames.
+    Gif,
+}
+
+impl FromStr for ImageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ppm" => Ok(ImageFormat::Ppm),
+            "pgm" => Ok(ImageFormat::Pgm),
+            "gif" => Ok(ImageFormat::Gif),
+            _ => Err(format!(
+                "'{}' is not a valid image format, expected ppm, pgm or gif",
+                s
+            )),
+        }
+    }
+}
+
+pub fn write_ppm(out: &mut impl Write, frame: &Frame, palette: &Palette) -> io::Result<()> {
+    write!(out, "P6\n{} {}\n255\n", frame.width, frame.height)?;
+    let pixels = frame
+        .pixels
+        .iter()
+        .flat_map(|&p| palette.colour(p))
+        .collect::<Vec<_>>();
+    out.write_all(&pixels)
+}
+
+pub fn write_pgm(out: &mut impl Write, frame: &Frame, palette: &Palette) -> io::Result<()> {
+    write!(out, "P5\n{} {}\n255\n", frame.width, frame.height)?;
+    let pixels = frame
+        .pixels
+        .iter()
+        .map(|&p| luminance(palette.colour(p)))
+        .collect::<Vec<_>>();
+    out.write_all(&pixels)
+}
+
+fn luminance([r, g, b]: Rgb) -> u8 {
+    ((299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000) as u8
+}
+
+/// Writes all frames as an endlessly looping GIF, showing each of them for `delay` hundredths of a
+/// second. Frames smaller than the largest one are centered on a background of the first colour.
+pub fn write_gif(
+    out: &mut impl Write,
+    frames: &[Frame],
+    palette: &Palette,
+    delay: u16,
+) -> io::Result<()> {
+    let width = frames.iter().map(Frame::width).max().unwrap_or(1);
+    let height = frames.iter().map(Frame::height).max().unwrap_or(1);
+    if width > u16::MAX as usize || height > u16::MAX as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{}x{} is too large for a GIF", width, height),
+        ));
+    }
+
+    let colours = palette
+        .colours
+        .iter()
+        .flatten()
+        .copied()
+        .collect::<Vec<_>>();
+    let mut encoder =
+        gif::Encoder::new(out, width as u16, height as u16, &colours).map_err(gif_error)?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(gif_error)?;
+
+    for frame in frames {
+        let frame = frame.centered_in(width, height, 0);
+        let pixels = frame
+            .pixels
+            .iter()
+            .map(|&p| palette.clamp(p))
+            .collect::<Vec<_>>();
+        let mut gif_frame =
+            gif::Frame::from_indexed_pixels(width as u16, height as u16, &pixels, None);
+        gif_frame.delay = delay;
+        encoder.write_frame(&gif_frame).map_err(gif_error)?;
+    }
+
+    Ok(())
+}
+
+fn gif_error(e: gif::EncodingError) -> io::Error {
+    match e {
+        gif::EncodingError::Io(e) => e,
+        e => io::Error::other(e),
+    }
+}
+
+/// Writes the frames into `path`: a directory of numbered images for PPM and PGM, or a single
+/// file for GIF.
+pub fn write_frames(
+    path: &Path,
+    frames: &[Frame],
+    palette: &Palette,
+    format: ImageFormat,
+    delay: u16,
+) -> io::Result<()> {
+    match format {
+        ImageFormat::Gif => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut out = io::BufWriter::new(fs::File::create(path)?);
+            write_gif(&mut out, frames, palette, delay)?;
+            out.flush()
+        }
+        ImageFormat::Ppm | ImageFormat::Pgm => {
+            fs::create_dir_all(path)?;
+            let extension = if format == ImageFormat::Ppm {
+                "ppm"
+            } else {
+                "pgm"
+            };
+            for (i, frame) in frames.iter().enumerate() {
+                let file = fs::File::create(path.join(format!("frame{:04}.{}", i, extension)))?;
+                let mut out = io::BufWriter::new(file);
+                if format == ImageFormat::Ppm {
+                    write_ppm(&mut out, frame, palette)?;
+                } else {
+                    write_pgm(&mut out, frame, palette)?;
+                }
+                out.flush()?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// The frames of a simulation, along with how they are best looked at.
+pub struct Animation {
+    pub frames: Vec<Frame>,
+    pub palette: Palette,
+    /// The factor the frames are enlarged by unless told otherwise, so small grids remain visible.
+    pub scale: usize,
+}
+
+/// The days whose simulations can be rendered.
+pub const ANIMATED_DAYS: [usize; 4] = [11, 13, 20, 25];
+
+/// Parses `input` and simulates `day` on it, recording a frame per step, or returns `None` if the
+/// day has nothing to animate.
+pub fn animate_day(day: usize, input: &str) -> Option<Result<Animation, Error>> {
+    let animation = match day {
+        11 => day11::Day11.parse(input).map(|grid| day11::animate(&grid)),
+        13 => day13::Day13
+            .parse(input)
+            .map(|(dots, folds)| day13::animate(&dots, &folds)),
+        20 => day20::Day20
+            .parse(input)
+            .map(|(algorithm, image)| day20::animate(&algorithm, &image)),
+        25 => day25::Day25
+            .parse(input)
+            .map(|floor| day25::animate(&floor)),
+        _ => return None,
+    };
+
+    Some(animation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkerboard() -> Frame {
+        Frame::from_fn(2, 2, |(x, y)| ((x + y) % 2) as u8)
+    }
+
+    #[test]
+    fn parse_palette() {
+        let palette = "#000000,ff8000".parse::<Palette>().unwrap();
+        assert_eq!(palette.colour(1), [255, 128, 0]);
+        // values past the end reuse the last colour
+        assert_eq!(palette.colour(7), [255, 128, 0]);
+        assert!("#00000".parse::<Palette>().is_err());
+        assert!("black".parse::<Palette>().is_err());
+    }
+
+    #[test]
+    fn gradient_includes_both_ends() {
+        let palette = Palette::gradient([0, 0, 0], [200, 100, 10], 3);
+        assert_eq!(palette.colour(0), [0, 0, 0]);
+        assert_eq!(palette.colour(1), [100, 50, 5]);
+        assert_eq!(palette.colour(2), [200, 100, 10]);
+    }
+
+    #[test]
+    fn ppm_and_pgm_encoding() {
+        let palette = Palette::new(vec![[0, 0, 0], [255, 255, 255]]);
+
+        let mut ppm = Vec::new();
+        write_ppm(&mut ppm, &checkerboard(), &palette).unwrap();
+        assert_eq!(&ppm[..11], b"P6\n2 2\n255\n");
+        assert_eq!(
+            &ppm[11..],
+            &[0, 0, 0, 255, 255, 255, 255, 255, 255, 0, 0, 0]
+        );
+
+        let mut pgm = Vec::new();
+        write_pgm(&mut pgm, &checkerboard(), &palette).unwrap();
+        assert_eq!(&pgm[..11], b"P5\n2 2\n255\n");
+        assert_eq!(&pgm[11..], &[0, 255, 255, 0]);
+    }
+
+    #[test]
+    fn scale_and_center_frames() {
+        let scaled = checkerboard().scaled(2);
+        assert_eq!((scaled.width(), scaled.height()), (4, 4));
+        assert_eq!(scaled.pixel((1, 1)), 0);
+        assert_eq!(scaled.pixel((2, 1)), 1);
+
+        let centered = checkerboard().centered_in(4, 3, 9);
+        assert_eq!(centered.pixel((0, 0)), 9);
+        assert_eq!(centered.pixel((1, 0)), 0);
+        assert_eq!(centered.pixel((2, 0)), 1);
+        assert_eq!(centered.pixel((1, 2)), 9);
+    }
+
+    #[test]
+    fn gif_of_differently_sized_frames() {
+        let palette = Palette::new(vec![[0, 0, 0], [255, 255, 255]]);
+        let mut gif = Vec::new();
+        write_gif(
+            &mut gif,
+            &[checkerboard(), checkerboard().scaled(2)],
+            &palette,
+            10,
+        )
+        .unwrap();
+        assert_eq!(&gif[..6], b"GIF89a");
+        // the logical screen fits the largest frame
+        assert_eq!(&gif[6..10], &[4, 0, 4, 0]);
+    }
+
+    #[test]
+    fn gif_reuses_the_last_colour_of_a_short_palette() {
+        let palette = Palette::new(vec![[0, 0, 0], [255, 255, 255]]);
+        let frame = Frame::from_fn(4, 1, |(x, _)| x as u8);
+        let mut gif = Vec::new();
+        write_gif(&mut gif, &[frame], &palette, 10).unwrap();
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = options.read_info(&gif[..]).unwrap();
+        let frame = decoder.read_next_frame().unwrap().unwrap();
+        assert_eq!(&frame.buffer[..], &[0, 1, 1, 1]);
+    }
+}
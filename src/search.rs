@@ -0,0 +1,219 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+};
+
+/// A graph whose edges are discovered on the fly, like the positions of a map or the states of a
+/// puzzle.
+pub trait Graph {
+    type State: Clone + Eq + Hash;
+
+    /// The states reachable from `state` in a single step, along with the cost of each step.
+    fn neighbours(&self, state: &Self::State) -> Vec<(Self::State, usize)>;
+}
+
+/// The states leading from the start to a goal, both included, and the total cost of the steps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<S> {
+    pub cost: usize,
+    pub states: Vec<S>,
+}
+
+/// Every state seen during a search, stored once and referred to by index everywhere else, so the
+/// states themselves need not be ordered.
+struct Visited<S> {
+    states: Vec<S>,
+    indices: HashMap<S, usize>,
+    parents: Vec<Option<usize>>,
+    costs: Vec<usize>,
+}
+
+impl<S: Clone + Eq + Hash> Visited<S> {
+    fn new(start: S) -> Self {
+        Self {
+            states: vec![start.clone()],
+            indices: HashMap::from([(start, 0)]),
+            parents: vec![None],
+            costs: vec![0],
+        }
+    }
+
+    /// Returns the index of `state`, and whether it has been seen before.
+    fn insert(&mut self, state: S) -> (usize, bool) {
+        if let Some(&index) = self.indices.get(&state) {
+            return (index, true);
+        }
+
+        let index = self.states.len();
+        self.states.push(state.clone());
+        self.indices.insert(state, index);
+        self.parents.push(None);
+        self.costs.push(usize::MAX);
+        (index, false)
+    }
+
+    fn path_to(&self, goal: usize) -> Path<S> {
+        let mut states = Vec::new();
+        let mut current = Some(goal);
+        while let Some(index) = current {
+            states.push(self.states[index].clone());
+            current = self.parents[index];
+        }
+        states.reverse();
+
+        Path {
+            cost: self.costs[goal],
+            states,
+        }
+    }
+}
+
+/// Finds the path with the fewest steps from `start` to a state satisfying `is_goal`, ignoring the
+/// cost of the steps; the cost of the returned path is the number of steps taken.
+pub fn bfs<G: Graph>(
+    graph: &G,
+    start: G::State,
+    is_goal: impl Fn(&G::State) -> bool,
+) -> Option<Path<G::State>> {
+    let mut visited = Visited::new(start);
+    let mut queue = VecDeque::from([0]);
+
+    while let Some(index) = queue.pop_front() {
+        if is_goal(&visited.states[index]) {
+            return Some(visited.path_to(index));
+        }
+
+        let steps = visited.costs[index] + 1;
+        for (neighbour, _) in graph.neighbours(&visited.states[index]) {
+            let (neighbour, seen) = visited.insert(neighbour);
+            if !seen {
+                visited.parents[neighbour] = Some(index);
+                visited.costs[neighbour] = steps;
+                queue.push_back(neighbour);
+            }
+        }
+    }
+
+    None
+}
+
+/// Finds the cheapest path from `start` to a state satisfying `is_goal`.
+pub fn dijkstra<G: Graph>(
+    graph: &G,
+    start: G::State,
+    is_goal: impl Fn(&G::State) -> bool,
+) -> Option<Path<G::State>> {
+    astar(graph, start, is_goal, |_| 0)
+}
+
+/// Finds the cheapest path from `start` to a state satisfying `is_goal`, exploring the states that
+/// `heuristic` deems closest to a goal first. The path is only guaranteed to be the cheapest if the
+/// heuristic never overestimates the remaining cost.
+pub fn astar<G: Graph>(
+    graph: &G,
+    start: G::State,
+    is_goal: impl Fn(&G::State) -> bool,
+    heuristic: impl Fn(&G::State) -> usize,
+) -> Option<Path<G::State>> {
+    let mut heap = BinaryHeap::from([Reverse((heuristic(&start), 0, 0))]);
+    let mut visited = Visited::new(start);
+
+    while let Some(Reverse((_, cost, index))) = heap.pop() {
+        // a cheaper way to this state has been found since it was queued
+        if cost > visited.costs[index] {
+            continue;
+        }
+        if is_goal(&visited.states[index]) {
+            return Some(visited.path_to(index));
+        }
+
+        for (neighbour, step_cost) in graph.neighbours(&visited.states[index]) {
+            let estimate = heuristic(&neighbour);
+            let (neighbour, _) = visited.insert(neighbour);
+            let neighbour_cost = cost + step_cost;
+            if neighbour_cost < visited.costs[neighbour] {
+                visited.parents[neighbour] = Some(index);
+                visited.costs[neighbour] = neighbour_cost;
+                heap.push(Reverse((
+                    neighbour_cost + estimate,
+                    neighbour_cost,
+                    neighbour,
+                )));
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A weighted, directed graph given as `(from, to, cost)` edges.
+    struct Edges(Vec<(char, char, usize)>);
+
+    impl Graph for Edges {
+        type State = char;
+
+        fn neighbours(&self, state: &char) -> Vec<(char, usize)> {
+            self.0
+                .iter()
+                .filter(|(from, _, _)| from == state)
+                .map(|&(_, to, cost)| (to, cost))
+                .collect()
+        }
+    }
+
+    fn example() -> Edges {
+        // the direct route is the shortest, the detour through `b` and `c` the cheapest
+        Edges(vec![
+            ('a', 'd', 10),
+            ('a', 'b', 1),
+            ('b', 'c', 2),
+            ('c', 'd', 3),
+            ('d', 'e', 1),
+        ])
+    }
+
+    #[test]
+    fn bfs_finds_fewest_steps() {
+        let path = bfs(&example(), 'a', |&s| s == 'd').unwrap();
+        assert_eq!(path.states, vec!['a', 'd']);
+        assert_eq!(path.cost, 1);
+    }
+
+    #[test]
+    fn dijkstra_finds_cheapest_path() {
+        let path = dijkstra(&example(), 'a', |&s| s == 'e').unwrap();
+        assert_eq!(path.states, vec!['a', 'b', 'c', 'd', 'e']);
+        assert_eq!(path.cost, 7);
+    }
+
+    #[test]
+    fn astar_with_admissible_heuristic_finds_cheapest_path() {
+        let remaining = |s: &char| match s {
+            'a' => 7,
+            'b' => 6,
+            'c' => 4,
+            'd' => 1,
+            _ => 0,
+        };
+        let path = astar(&example(), 'a', |&s| s == 'e', remaining).unwrap();
+        assert_eq!(path.cost, 7);
+    }
+
+    #[test]
+    fn unreachable_goal() {
+        assert!(bfs(&example(), 'd', |&s| s == 'a').is_none());
+        assert!(dijkstra(&example(), 'e', |&s| s == 'a').is_none());
+    }
+
+    #[test]
+    fn start_can_be_the_goal() {
+        let path = dijkstra(&example(), 'a', |&s| s == 'a').unwrap();
+        assert_eq!(path.states, vec!['a']);
+        assert_eq!(path.cost, 0);
+    }
+}
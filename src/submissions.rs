@@ -0,0 +1,211 @@
+use crate::{website::Verdict, Error, Part};
+use std::{collections::HashMap, fs, io, path::Path};
+
+/// The record `aoc submit` keeps when no other one is given.
+pub const DEFAULT_SUBMISSIONS_PATH: &str = "submissions.toml";
+
+/// The answers submitted so far and the final verdicts on them, kept in a TOML record of the form:
+///
+/// ```toml
+/// [day1]
+/// part1 = { "1400" = "too high", "1393" = "correct" }
+/// ```
+///
+/// The website makes every wrong answer cost a growing wait, so the record is checked before
+/// submitting, to never spend one on an answer it is known to reject.
+#[derive(Debug, Default)]
+pub struct Submissions {
+    verdicts: HashMap<(usize, Part), Vec<(String, Verdict)>>,
+}
+
+impl Submissions {
+    /// Loads the record at `path`, which is empty if nothing has been submitted yet.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(source) => {
+                return Err(Error::Io {
+                    path: path.to_path_buf(),
+                    source,
+                }
+                .to_string())
+            }
+        };
+
+        Self::parse(&contents).map_err(|e| format!("could not parse '{}': {}", path.display(), e))
+    }
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let record = s.parse::<toml::Value>().map_err(|e| e.to_string())?;
+        let days = record.as_table().ok_or("expected a table of days")?;
+
+        let mut verdicts = HashMap::new();
+        for (day_key, parts) in days {
+            let day = day_key
+                .strip_prefix("day")
+                .and_then(|day| day.parse::<usize>().ok())
+                .ok_or_else(|| format!("'{}' is not a day of the form 'dayN'", day_key))?;
+            let parts = parts
+                .as_table()
+                .ok_or_else(|| format!("'{}' is not a table of parts", day_key))?;
+
+            for (part_key, answers) in parts {
+                let part = match part_key.as_str() {
+                    "part1" => Part::One,
+                    "part2" => Part::Two,
+                    _ => {
+                        return Err(format!(
+                            "'{}.{}' is neither 'part1' nor 'part2'",
+                            day_key, part_key
+                        ))
+                    }
+                };
+                let answers = answers.as_table().ok_or_else(|| {
+                    format!("'{}.{}' is not a table of answers", day_key, part_key)
+                })?;
+
+                let mut part_verdicts = Vec::new();
+                for (answer, verdict) in answers {
+                    let verdict = verdict
+                        .as_str()
+                        .ok_or_else(|| format!("the verdict on '{}' is not a string", answer))?
+                        .parse()?;
+                    part_verdicts.push((answer.clone(), verdict));
+                }
+                verdicts.insert((day, part), part_verdicts);
+            }
+        }
+
+        Ok(Self { verdicts })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        fs::write(path, self.to_toml()).map_err(|source| {
+            Error::Io {
+                path: path.to_path_buf(),
+                source,
+            }
+            .to_string()
+        })
+    }
+
+    fn to_toml(&self) -> String {
+        let mut days = toml::value::Table::new();
+        for ((day, part), part_verdicts) in &self.verdicts {
+            let answers = part_verdicts
+                .iter()
+                .map(|(answer, verdict)| (answer.clone(), toml::Value::from(verdict.to_string())))
+                .collect::<toml::value::Table>();
+            let parts = days
+                .entry(format!("day{}", day))
+                .or_insert_with(|| toml::Value::Table(toml::value::Table::new()));
+            if let toml::Value::Table(parts) = parts {
+                parts.insert(format!("part{}", part), toml::Value::Table(answers));
+            }
+        }
+
+        toml::Value::Table(days).to_string()
+    }
+
+    /// Explains why submitting `answer` would be a waste, if it would be: because the part is
+    /// solved already, the answer was judged before, or it is beyond an answer known to be too
+    /// high or too low.
+    pub fn check(&self, day: usize, part: Part, answer: &str) -> Result<(), String> {
+        let part_verdicts = match self.verdicts.get(&(day, part)) {
+            Some(part_verdicts) => part_verdicts,
+            None => return Ok(()),
+        };
+
+        for (submitted, verdict) in part_verdicts {
+            if *verdict == Verdict::Correct {
+                return Err(format!(
+                    "day {} part {} was solved already, with {}",
+                    day, part, submitted
+                ));
+            }
+            if submitted == answer {
+                return Err(format!(
+                    "{} was submitted already, and was {}",
+                    answer, verdict
+                ));
+            }
+            if let (Ok(submitted_value), Ok(value)) =
+                (submitted.parse::<i64>(), answer.parse::<i64>())
+            {
+                let beyond = match verdict {
+                    Verdict::TooHigh => value >= submitted_value,
+                    Verdict::TooLow => value <= submitted_value,
+                    _ => false,
+                };
+                if beyond {
+                    return Err(format!(
+                        "{} is {} as well, as {} was",
+                        answer, verdict, submitted
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records the verdict on a submitted answer, if it is a final one.
+    pub fn record(&mut self, day: usize, part: Part, answer: &str, verdict: Verdict) {
+        if verdict.is_final() {
+            self.verdicts
+                .entry((day, part))
+                .or_default()
+                .push((answer.to_string(), verdict));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const RECORD: &str = "\
+[day1]
+part1 = { \"1400\" = \"too high\", \"1300\" = \"too low\" }
+part2 = { \"1359\" = \"correct\" }
+";
+
+    #[test]
+    fn answers_known_to_be_rejected_are_not_submitted_again() {
+        let submissions = Submissions::parse(RECORD).unwrap();
+        assert!(submissions.check(1, Part::One, "1393").is_ok());
+        assert!(submissions.check(1, Part::One, "1400").is_err());
+        assert!(submissions.check(1, Part::One, "1500").is_err());
+        assert!(submissions.check(1, Part::One, "1200").is_err());
+        assert!(submissions.check(1, Part::Two, "1360").is_err());
+        assert!(submissions.check(2, Part::One, "1400").is_ok());
+    }
+
+    #[test]
+    fn only_final_verdicts_are_recorded() {
+        let mut submissions = Submissions::default();
+        submissions.record(3, Part::One, "7", Verdict::Wait(Duration::from_secs(60)));
+        assert!(submissions.check(3, Part::One, "7").is_ok());
+        submissions.record(3, Part::One, "7", Verdict::Incorrect);
+        assert!(submissions.check(3, Part::One, "7").is_err());
+    }
+
+    #[test]
+    fn record_survives_a_round_trip() {
+        let mut submissions = Submissions::parse(RECORD).unwrap();
+        submissions.record(13, Part::Two, "ABCDEFGH", Verdict::Correct);
+
+        let reloaded = Submissions::parse(&submissions.to_toml()).unwrap();
+        assert!(reloaded.check(1, Part::One, "1500").is_err());
+        assert!(reloaded.check(13, Part::Two, "HGFEDCBA").is_err());
+    }
+
+    #[test]
+    fn parse_rejects_unknown_verdicts() {
+        assert!(Submissions::parse("[day1]\npart1 = { \"1\" = \"maybe\" }").is_err());
+    }
+}
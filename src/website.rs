@@ -0,0 +1,486 @@
+use crate::{resolve_input_path, Error, Part};
+use std::{
+    env,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+/// Environment variable holding the session token, taking precedence over the config file.
+pub const SESSION_VAR: &str = "AOC_SESSION";
+
+/// Environment variable holding the URL of the website, taking precedence over the config file.
+pub const BASE_URL_VAR: &str = "AOC_BASE_URL";
+
+/// Environment variable that points to the config file when it is not `aoc.toml`.
+pub const CONFIG_VAR: &str = "AOC_CONFIG";
+
+const DEFAULT_CONFIG_PATH: &str = "aoc.toml";
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+const YEAR: u16 = 2021;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How to reach the website, read from a TOML config file of the form:
+///
+/// ```toml
+/// session = "53616c7465645f5f..."
+/// base_url = "https://adventofcode.com"
+/// ```
+///
+/// Both keys are optional, and both can be overridden from the environment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// The value of the `session` cookie of a logged in browser.
+    pub session: Option<String>,
+    pub base_url: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            session: None,
+            base_url: DEFAULT_BASE_URL.to_string(),
+        }
+    }
+}
+
+impl Config {
+    /// Reads the config file in [`CONFIG_VAR`] or `aoc.toml`, if there is one, then applies the
+    /// overrides of the environment.
+    pub fn load() -> Result<Self, String> {
+        let path = env::var_os(CONFIG_VAR)
+            .map_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH), PathBuf::from);
+        let mut config = match fs::read_to_string(&path) {
+            Ok(contents) => Self::parse(&contents)
+                .map_err(|e| format!("could not parse '{}': {}", path.display(), e))?,
+            Err(_) if !path.exists() => Self::default(),
+            Err(source) => return Err(Error::Io { path, source }.to_string()),
+        };
+
+        if let Ok(session) = env::var(SESSION_VAR) {
+            config.session = Some(session);
+        }
+        if let Ok(base_url) = env::var(BASE_URL_VAR) {
+            config.base_url = base_url;
+        }
+
+        Ok(config)
+    }
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let config = s.parse::<toml::Value>().map_err(|e| e.to_string())?;
+        let config = config.as_table().ok_or("expected a table")?;
+
+        let string = |key: &str| match config.get(key) {
+            Some(toml::Value::String(value)) => Ok(Some(value.trim().to_string())),
+            Some(_) => Err(format!("'{}' is not a string", key)),
+            None => Ok(None),
+        };
+        if let Some(key) = config
+            .keys()
+            .find(|key| !["session", "base_url"].contains(&key.as_str()))
+        {
+            return Err(format!("'{}' is neither 'session' nor 'base_url'", key));
+        }
+
+        Ok(Self {
+            session: string("session")?,
+            base_url: string("base_url")?.unwrap_or_else(|| DEFAULT_BASE_URL.to_string()),
+        })
+    }
+}
+
+/// What the website made of a submitted answer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong, without a hint which way.
+    Incorrect,
+    /// Answers are rate limited, and this one was not even looked at.
+    Wait(Duration),
+    /// The part is solved already, so there is nothing to submit to.
+    AlreadySolved,
+}
+
+impl Verdict {
+    /// Reads the verdict from the page the website answers a submission with.
+    pub fn parse(page: &str) -> Result<Self, String> {
+        if page.contains("That's the right answer") {
+            Ok(Verdict::Correct)
+        } else if page.contains("That's not the right answer") {
+            Ok(if page.contains("your answer is too high") {
+                Verdict::TooHigh
+            } else if page.contains("your answer is too low") {
+                Verdict::TooLow
+            } else {
+                Verdict::Incorrect
+            })
+        } else if let Some((_, rest)) = page.split_once("You have ") {
+            // "You have 5m 2s left to wait."
+            let (wait, _) = rest
+                .split_once(" left to wait")
+                .ok_or("the time left to wait is missing")?;
+            parse_wait(wait).map(Verdict::Wait)
+        } else if page.contains("You don't seem to be solving the right level") {
+            Ok(Verdict::AlreadySolved)
+        } else {
+            Err("the response is not one of a submitted answer".to_string())
+        }
+    }
+
+    /// Whether the verdict is on the answer itself, and so holds for every later submission of it.
+    pub fn is_final(self) -> bool {
+        matches!(
+            self,
+            Verdict::Correct | Verdict::TooHigh | Verdict::TooLow | Verdict::Incorrect
+        )
+    }
+}
+
+/// Parses a duration of the form `1h 5m 2s`, where any of the units may be missing.
+fn parse_wait(s: &str) -> Result<Duration, String> {
+    s.split_whitespace()
+        .map(|amount| {
+            let unit = match amount.chars().last() {
+                Some('h') => 3600,
+                Some('m') => 60,
+                Some('s') => 1,
+                _ => return Err(format!("'{}' is not an amount of time", amount)),
+            };
+            amount[..amount.len() - 1]
+                .parse::<u64>()
+                .map(|n| n * unit)
+                .map_err(|_| format!("'{}' is not an amount of time", amount))
+        })
+        .sum::<Result<u64, _>>()
+        .map(Duration::from_secs)
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Correct => write!(f, "correct"),
+            Verdict::TooHigh => write!(f, "too high"),
+            Verdict::TooLow => write!(f, "too low"),
+            Verdict::Incorrect => write!(f, "incorrect"),
+            Verdict::Wait(duration) => write!(f, "wait {}s", duration.as_secs()),
+            Verdict::AlreadySolved => write!(f, "already solved"),
+        }
+    }
+}
+
+/// Parses the final verdicts, the only ones worth recording.
+impl FromStr for Verdict {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "correct" => Ok(Verdict::Correct),
+            "too high" => Ok(Verdict::TooHigh),
+            "too low" => Ok(Verdict::TooLow),
+            "incorrect" => Ok(Verdict::Incorrect),
+            _ => Err(format!(
+                "'{}' is not a verdict, expected correct, too high, too low or incorrect",
+                s
+            )),
+        }
+    }
+}
+
+/// A client of the website, logged in with a session token.
+pub struct Website {
+    agent: ureq::Agent,
+    base_url: String,
+    session: String,
+}
+
+impl Website {
+    pub fn new(config: &Config) -> Result<Self, String> {
+        let session = config.session.clone().ok_or_else(|| {
+            format!(
+                "there is no session token, set {} or 'session' in the config file",
+                SESSION_VAR
+            )
+        })?;
+
+        Ok(Self {
+            agent: ureq::AgentBuilder::new()
+                .timeout(TIMEOUT)
+                .user_agent(concat!(
+                    env!("CARGO_PKG_NAME"),
+                    "/",
+                    env!("CARGO_PKG_VERSION")
+                ))
+                .build(),
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            session,
+        })
+    }
+
+    fn url(&self, day: usize, page: &str) -> String {
+        format!("{}/{}/day/{}{}", self.base_url, YEAR, day, page)
+    }
+
+    /// Downloads the puzzle input of `day`.
+    pub fn fetch_input(&self, day: usize) -> Result<String, String> {
+        let url = self.url(day, "/input");
+        let response = self
+            .agent
+            .get(&url)
+            .set("Cookie", &format!("session={}", self.session))
+            .call()
+            .map_err(|e| match e {
+                ureq::Error::Status(404, _) => format!("the input of day {} is not out yet", day),
+                // the website answers a missing or expired session with a bad request
+                ureq::Error::Status(400, _) | ureq::Error::Status(401, _) => {
+                    "the session token was rejected, it may have expired".to_string()
+                }
+                e => format!("could not fetch '{}': {}", url, e),
+            })?;
+
+        response
+            .into_string()
+            .map_err(|e| format!("could not read the input from '{}': {}", url, e))
+    }
+
+    /// Submits `answer` to `part` of `day`.
+    pub fn submit(&self, day: usize, part: Part, answer: &str) -> Result<Verdict, String> {
+        let url = self.url(day, "/answer");
+        let response = self
+            .agent
+            .post(&url)
+            .set("Cookie", &format!("session={}", self.session))
+            .send_form(&[("level", &part.to_string()), ("answer", answer)])
+            .map_err(|e| match e {
+                ureq::Error::Status(400, _) | ureq::Error::Status(401, _) => {
+                    "the session token was rejected, it may have expired".to_string()
+                }
+                e => format!("could not submit to '{}': {}", url, e),
+            })?;
+        let page = response
+            .into_string()
+            .map_err(|e| format!("could not read the response of '{}': {}", url, e))?;
+
+        Verdict::parse(&page)
+    }
+}
+
+/// What fetching an input ended up doing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fetched {
+    Downloaded(PathBuf),
+    /// The input was there already, and was left alone.
+    Existing(PathBuf),
+}
+
+/// Downloads the input of `day` into the input directory, unless it is there already: inputs never
+/// change, so each one is only ever downloaded once.
+pub fn fetch_input(config: &Config, day: usize) -> Result<Fetched, String> {
+    fetch_input_into(config, day, &resolve_input_path(day, None))
+}
+
+/// Like [`fetch_input`], but into `path` rather than the input directory.
+pub fn fetch_input_into(config: &Config, day: usize, path: &Path) -> Result<Fetched, String> {
+    if path.exists() {
+        return Ok(Fetched::Existing(path.to_path_buf()));
+    }
+
+    let input = Website::new(config)?.fetch_input(day)?;
+    write_input(path, &input)?;
+
+    Ok(Fetched::Downloaded(path.to_path_buf()))
+}
+
+fn write_input(path: &Path, input: &str) -> Result<(), String> {
+    let io_error = |source| {
+        Error::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+        .to_string()
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(io_error)?;
+    }
+    fs::write(path, input).map_err(io_error)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        thread::{self, JoinHandle},
+    };
+
+    /// Starts a stub of the website on a free local port that answers a single request with
+    /// `status` and `body`, returning its URL and the request it received.
+    pub(crate) fn serve_once(status: &str, body: &str) -> (String, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 1024];
+            // a request is complete after the blank line ending its headers, plus its body
+            while !is_complete(&request) {
+                let n = stream.read(&mut buffer).unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buffer[..n]);
+            }
+            stream.write_all(response.as_bytes()).unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        (url, server)
+    }
+
+    fn is_complete(request: &[u8]) -> bool {
+        let request = String::from_utf8_lossy(request);
+        match request.split_once("\r\n\r\n") {
+            Some((headers, body)) => {
+                let content_length = headers
+                    .lines()
+                    .find_map(|line| {
+                        line.to_ascii_lowercase()
+                            .strip_prefix("content-length: ")
+                            .map(str::to_string)
+                    })
+                    .map_or(0, |length| length.trim().parse().unwrap());
+                body.len() >= content_length
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn config(base_url: &str) -> Config {
+        Config {
+            session: Some("secret".to_string()),
+            base_url: base_url.to_string(),
+        }
+    }
+
+    #[test]
+    fn config_parses_both_keys() {
+        let config = Config::parse("session = \"abc\"\nbase_url = \"http://localhost:8080\"\n");
+        assert_eq!(
+            config,
+            Ok(Config {
+                session: Some("abc".to_string()),
+                base_url: "http://localhost:8080".to_string(),
+            })
+        );
+        assert_eq!(Config::parse(""), Ok(Config::default()));
+        assert!(Config::parse("token = \"abc\"").is_err());
+    }
+
+    #[test]
+    fn fetch_input_sends_the_session_cookie() {
+        let (url, server) = serve_once("200 OK", "1\n2\n3\n");
+        let input = Website::new(&config(&url)).unwrap().fetch_input(7).unwrap();
+
+        assert_eq!(input, "1\n2\n3\n");
+        let request = server.join().unwrap();
+        assert!(request.starts_with("GET /2021/day/7/input HTTP/1.1\r\n"));
+        assert!(request.contains("\r\nCookie: session=secret\r\n"));
+    }
+
+    #[test]
+    fn fetch_input_explains_a_missing_input() {
+        let (url, server) = serve_once("404 Not Found", "");
+        let e = Website::new(&config(&url))
+            .unwrap()
+            .fetch_input(25)
+            .unwrap_err();
+
+        assert_eq!(e, "the input of day 25 is not out yet");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn submit_posts_the_answer_and_parses_the_verdict() {
+        let page = "<main><article><p>That's not the right answer; your answer is too low. \
+            Please wait one minute before trying again.</p></article></main>";
+        let (url, server) = serve_once("200 OK", page);
+        let verdict = Website::new(&config(&url))
+            .unwrap()
+            .submit(4, Part::Two, "1924");
+
+        assert_eq!(verdict, Ok(Verdict::TooLow));
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /2021/day/4/answer HTTP/1.1\r\n"));
+        assert!(request.contains("\r\nCookie: session=secret\r\n"));
+        assert!(request.ends_with("\r\n\r\nlevel=2&answer=1924"));
+    }
+
+    #[test]
+    fn verdicts_are_parsed_from_the_response() {
+        let verdict = |page| Verdict::parse(page);
+        assert_eq!(
+            verdict("<p>That's the right answer!  You are one gold star closer.</p>"),
+            Ok(Verdict::Correct)
+        );
+        assert_eq!(
+            verdict("<p>That's not the right answer; your answer is too high.</p>"),
+            Ok(Verdict::TooHigh)
+        );
+        assert_eq!(
+            verdict("<p>That's not the right answer.  If you're stuck, ...</p>"),
+            Ok(Verdict::Incorrect)
+        );
+        assert_eq!(
+            verdict("<p>You gave an answer too recently.  You have 5m 2s left to wait.</p>"),
+            Ok(Verdict::Wait(Duration::from_secs(302)))
+        );
+        assert_eq!(
+            verdict("<p>You have 39s left to wait.</p>"),
+            Ok(Verdict::Wait(Duration::from_secs(39)))
+        );
+        assert_eq!(
+            verdict("<p>You don't seem to be solving the right level.  Did you already complete it?</p>"),
+            Ok(Verdict::AlreadySolved)
+        );
+        assert!(verdict("<p>Something else entirely</p>").is_err());
+    }
+
+    #[test]
+    fn website_needs_a_session() {
+        assert!(Website::new(&Config::default()).is_err());
+    }
+
+    #[test]
+    fn missing_input_is_downloaded_once() {
+        let dir = env::temp_dir().join(format!("aoc-fetch-{}", std::process::id()));
+        let path = dir.join("input").join("day3.txt");
+
+        let (url, server) = serve_once("200 OK", "00100\n");
+        let fetched = fetch_input_into(&config(&url), 3, &path);
+        assert_eq!(fetched, Ok(Fetched::Downloaded(path.clone())));
+        server.join().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "00100\n");
+
+        // the stub is gone, so downloading again would fail
+        let fetched = fetch_input_into(&config(&url), 3, &path);
+        assert_eq!(fetched, Ok(Fetched::Existing(path.clone())));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "00100\n");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}